@@ -0,0 +1,79 @@
+//! Edit distance between names, used to suggest a name close to a misspelled
+//! one.
+
+/// Returns the edit distance between `a` and `b`, the count of characters to
+/// insert, delete, substitute or swap with the next one to change `a` into
+/// `b`, a swap being a common typo.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // distances between the prefixes of `a` of the two previous lengths and
+    // each prefix of `b`.
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j - 1] + cost).min(prev[j] + 1).min(row[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        prev2 = std::mem::replace(&mut prev, row);
+    }
+
+    prev[b.len()]
+}
+
+/// Returns the candidate closest to `name`, if one is close enough to be a
+/// misspelling of it. A candidate differing only by the case always matches
+/// and the ties are broken by the alphabetical order so that the result
+/// doesn't depend on the order of the candidates.
+pub fn find_best_match<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_dist = name.chars().count().max(3) / 3;
+
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        if candidate == name {
+            continue;
+        }
+        let dist = if candidate.to_lowercase() == name.to_lowercase() {
+            0
+        } else {
+            edit_distance(name, candidate)
+        };
+        if dist > max_dist {
+            continue;
+        }
+        if best.is_none_or(|b| (dist, candidate) < b) {
+            best = Some((dist, candidate));
+        }
+    }
+
+    best.map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("🌹a", "🌹b"), 1);
+        assert_eq!(edit_distance("ture", "true"), 1);
+
+        let names = ["count", "counter", "amount", "Count"];
+        assert_eq!(find_best_match("cont", names), Some("count"));
+        assert_eq!(find_best_match("COUNTER", names), Some("counter"));
+        assert_eq!(find_best_match("countr", names), Some("count"));
+        assert_eq!(find_best_match("x", names), None);
+        assert_eq!(find_best_match("count", ["count"]), None);
+    }
+}
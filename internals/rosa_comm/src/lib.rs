@@ -1,11 +1,10 @@
 //! Common utilities and data structures used in the compiler.
 
-use std::{
-    collections::HashMap,
-    ops::{Add, AddAssign, Range, RangeInclusive, Sub},
-};
+use std::ops::{Add, AddAssign, Range, RangeInclusive, Sub};
 
+pub mod edit_distance;
 pub mod prelude;
+pub mod source_map;
 
 type BytePosInner = u32;
 
@@ -123,17 +122,28 @@ impl Default for Span {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MultiSpan {
     pub(crate) primary_spans: Vec<Span>,
-    // TODO: Implement this part of the MultiSpan type, for it's not one of the
-    // most important thing to worry about.
-    // pub(crate) span_labels: Vec<(DiagSpan, DiagMessage)>,
+    /// The labels of the spans, a labelled span that isn't one of the primary
+    /// spans is a secondary span.
+    pub(crate) span_labels: Vec<(Span, String)>,
+}
+
+/// A span of a `MultiSpan`, with its label if it has one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpanLabel {
+    pub span: Span,
+    pub is_primary: bool,
+    pub label: Option<String>,
 }
 
 impl MultiSpan {
     pub fn from_spans(primary_spans: Vec<Span>) -> MultiSpan {
-        MultiSpan { primary_spans }
+        MultiSpan {
+            primary_spans,
+            span_labels: Vec::new(),
+        }
     }
 
     pub fn primary(&self) -> &Span {
@@ -145,68 +155,37 @@ impl MultiSpan {
     pub fn primaries(&self) -> &Vec<Span> {
         &self.primary_spans
     }
+
+    pub fn push_span_label(&mut self, span: Span, label: impl Into<String>) {
+        self.span_labels.push((span, label.into()));
+    }
+
+    /// Returns the labelled spans and the primary spans without label.
+    pub fn span_labels(&self) -> Vec<SpanLabel> {
+        let mut labels = self
+            .primary_spans
+            .iter()
+            .filter(|span| !self.span_labels.iter().any(|(s, _)| s == *span))
+            .map(|span| SpanLabel {
+                span: span.clone(),
+                is_primary: true,
+                label: None,
+            })
+            .collect::<Vec<_>>();
+
+        labels.extend(self.span_labels.iter().map(|(span, label)| SpanLabel {
+            span: span.clone(),
+            is_primary: self.primary_spans.contains(span),
+            label: Some(label.clone()),
+        }));
+        labels
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct LineCol {
     /// Line number, starting from one.
     pub line: u32,
     /// Column number, starting from one.
     pub col: u32,
 }
-
-pub type FullLinePos = Range<LineCol>;
-
-#[derive(Debug, Default)]
-pub struct LinesData {
-    /// key = line number
-    /// value = a list of spans inside that line.
-    p: HashMap<u32, Vec<Range<u32>>>,
-}
-
-impl LinesData {
-    pub fn new() -> LinesData {
-        LinesData { p: HashMap::new() }
-    }
-
-    pub fn insert_inline(&mut self, line: u32, span: Range<u32>) -> Option<()> {
-        let spans = self.p.get_mut(&line)?;
-        spans.push(span);
-
-        Some(())
-    }
-
-    pub fn push(&mut self, line: u32, poses: Vec<Range<u32>>) -> Option<()> {
-        self.p.insert(line, poses)?;
-        Some(())
-    }
-
-    pub fn lines(&self) -> Vec<u32> {
-        let mut v: Vec<u32> = self.p.keys().copied().collect();
-        v.sort();
-        v
-    }
-
-    pub fn contains_line(&self, line: u32) -> bool {
-        self.p.contains_key(&line)
-    }
-
-    pub fn get(&self, line: u32) -> Vec<Range<u32>> {
-        let mut v: Vec<_> = self
-            .p
-            .get(&line)
-            .expect("This line does not exist.")
-            .clone();
-        v.sort_by(|a, b| a.clone().cmp(b.clone()));
-        v
-    }
-
-    pub fn push_or_append(&mut self, line: u32, r: Range<u32>) -> Option<()> {
-        if self.contains_line(line) {
-            self.insert_inline(line, r)?;
-        } else {
-            self.push(line, vec![r]);
-        }
-        Some(())
-    }
-}
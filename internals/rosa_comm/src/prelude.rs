@@ -1,4 +1,5 @@
 //! Prelude of the common lib used to reduce the lines due to 'use' items and
 //! make it cleaner.
 
-pub use crate::{BytePos, FullLinePos, LineCol, LinesData, MultiSpan, Span};
+pub use crate::source_map::{SourceFile, SourceMap};
+pub use crate::{BytePos, LineCol, MultiSpan, Span, SpanLabel};
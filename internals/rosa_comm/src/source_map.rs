@@ -0,0 +1,203 @@
+//! Source map of the compiler, it owns the source files and maps the global
+//! byte positions of the spans back to the file they belong to.
+
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{BytePos, LineCol};
+
+/// A source file registered in the source map, with the index of the start
+/// of its lines.
+#[derive(Debug)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub src: String,
+    /// Global position of the first byte of the file.
+    pub start_pos: BytePos,
+    /// Byte offsets of the start of each line, relative to the start of the
+    /// file, the first one is always zero.
+    lines: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(path: PathBuf, src: String, start_pos: BytePos) -> SourceFile {
+        let lines = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        SourceFile {
+            path,
+            src,
+            start_pos,
+            lines,
+        }
+    }
+
+    /// Global position of the end of the file.
+    pub fn end_pos(&self) -> BytePos {
+        self.start_pos + self.src.len().into()
+    }
+
+    /// Returns true if the global position is in the file, the end of the file
+    /// included.
+    pub fn contains(&self, pos: BytePos) -> bool {
+        self.start_pos <= pos && pos <= self.end_pos()
+    }
+
+    /// Returns the count of lines in the file.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the line and the column of the global position, the column
+    /// counts the characters, not the bytes, from the start of the line.
+    pub fn line_col(&self, pos: BytePos) -> LineCol {
+        let mut idx = usize::from(pos - self.start_pos).min(self.src.len());
+        // a position inside of a character points to the character.
+        while !self.src.is_char_boundary(idx) {
+            idx -= 1;
+        }
+
+        let line = self.lines.partition_point(|&start| start <= idx);
+        let start = self.lines[line - 1];
+        let col = self.src[start..idx].chars().count() + 1;
+
+        LineCol {
+            line: line as u32,
+            col: col as u32,
+        }
+    }
+
+    /// Returns the content of the `line`, starting from one, without the line
+    /// terminator.
+    pub fn line(&self, line: u32) -> Option<&str> {
+        let idx = (line as usize).checked_sub(1)?;
+        let start = *self.lines.get(idx)?;
+        let end = self.lines.get(idx + 1).copied().unwrap_or(self.src.len());
+
+        let text = &self.src[start..end];
+        let text = text.strip_suffix('\n').unwrap_or(text);
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+
+    /// Returns the length, in characters, of the `line`.
+    pub fn line_width(&self, line: u32) -> Option<usize> {
+        self.line(line).map(|s| s.chars().count())
+    }
+}
+
+/// The source files of a compilation, each file has its own range of global
+/// byte positions so that a `Span` is enough to know the file it points to.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: RefCell<Vec<Rc<SourceFile>>>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    /// Registers a new source file, its positions start after the end of the
+    /// last file registered.
+    pub fn add_file(&self, path: impl Into<PathBuf>, src: impl Into<String>) -> Rc<SourceFile> {
+        let mut files = self.files.borrow_mut();
+        // one byte is left between the files, so that the end of a file is
+        // not the start of the next one.
+        let start_pos = files
+            .last()
+            .map(|f| f.end_pos() + BytePos(1))
+            .unwrap_or(BytePos::ZERO);
+
+        let file = Rc::new(SourceFile::new(path.into(), src.into(), start_pos));
+        files.push(file.clone());
+        file
+    }
+
+    /// Returns the file containing the global position.
+    pub fn lookup_file(&self, pos: BytePos) -> Option<Rc<SourceFile>> {
+        let files = self.files.borrow();
+        let idx = files
+            .partition_point(|f| f.start_pos <= pos)
+            .checked_sub(1)?;
+        let file = &files[idx];
+        file.contains(pos).then(|| file.clone())
+    }
+
+    /// Returns the file, the line and the column of the global position.
+    pub fn lookup_line_col(&self, pos: BytePos) -> Option<(Rc<SourceFile>, LineCol)> {
+        let file = self.lookup_file(pos)?;
+        let lc = file.line_col(pos);
+        Some((file, lc))
+    }
+
+    /// Returns the file registered with this path.
+    pub fn file_by_path(&self, path: &Path) -> Option<Rc<SourceFile>> {
+        self.files.borrow().iter().find(|f| f.path == path).cloned()
+    }
+
+    /// Returns the files registered, in order.
+    pub fn files(&self) -> Vec<Rc<SourceFile>> {
+        self.files.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_map_lookup() {
+        let sm = SourceMap::new();
+        let a = sm.add_file("a.ro", "fun a() =\n    1\n");
+        let b = sm.add_file("b.ro", "fun b() =\n    2\n");
+        assert_eq!(a.start_pos, BytePos(0));
+        assert_eq!(b.start_pos, a.end_pos() + BytePos(1));
+
+        let (file, lc) = sm.lookup_line_col(BytePos(14)).unwrap();
+        assert_eq!(file.path.to_str(), Some("a.ro"));
+        assert_eq!((lc.line, lc.col), (2, 5));
+
+        let (file, lc) = sm.lookup_line_col(b.start_pos + BytePos(4)).unwrap();
+        assert_eq!(file.path.to_str(), Some("b.ro"));
+        assert_eq!((lc.line, lc.col), (1, 5));
+
+        // the end of a file belongs to it
+        let (file, _) = sm.lookup_line_col(a.end_pos()).unwrap();
+        assert_eq!(file.path.to_str(), Some("a.ro"));
+        assert!(sm.lookup_file(b.end_pos() + BytePos(1)).is_none());
+    }
+
+    #[test]
+    fn source_file_line_index() {
+        let sm = SourceMap::new();
+        let file = sm.add_file("utf8.ro", "let a = 'é'\r\n# 🌹 rose\n\nend");
+        assert_eq!(file.line_count(), 4);
+
+        assert_eq!(file.line(1), Some("let a = 'é'"));
+        assert_eq!(file.line(2), Some("# 🌹 rose"));
+        assert_eq!(file.line(3), Some(""));
+        assert_eq!(file.line(4), Some("end"));
+        assert_eq!(file.line(0), None);
+        assert_eq!(file.line(5), None);
+        assert_eq!(file.line_width(2), Some(8));
+
+        let lc = |pos: usize| {
+            let LineCol { line, col } = file.line_col(BytePos::from(pos));
+            (line, col)
+        };
+        assert_eq!(lc(0), (1, 1));
+        // the closing quote is after the two bytes of `é`
+        assert_eq!(lc(11), (1, 11));
+        assert_eq!(lc(14), (2, 1));
+        // `r` of `rose` is after the four bytes of the emoji
+        assert_eq!(lc(21), (2, 5));
+        // a position inside of the emoji points to it
+        assert_eq!(lc(17), (2, 3));
+        assert_eq!(lc(26), (3, 1));
+        assert_eq!(lc(30), (4, 4));
+    }
+}
@@ -0,0 +1,93 @@
+//! Error codes of the diagnostics and their long-form explanations, shown by
+//! `rosac --explain E0001`.
+//!
+//! The explanation of an error code is written in the file
+//! `error_codes/E0001.md`, with an erroneous example and how to fix it. A code
+//! is never reused, even if the error it describes is no longer emitted.
+
+use std::fmt;
+
+/// A stable code identifying a kind of error, e.g: `E0019`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ErrCode(u32);
+
+impl ErrCode {
+    /// Parses an error code written like `E0019`, the `E` may be omitted.
+    pub fn parse(s: &str) -> Option<ErrCode> {
+        let digits = s.strip_prefix(['E', 'e']).unwrap_or(s);
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(ErrCode(digits.parse().ok()?))
+    }
+
+    /// Returns the long-form explanation of the error code, if it is a code
+    /// of the registry.
+    pub fn explanation(self) -> Option<&'static str> {
+        REGISTRY
+            .iter()
+            .find(|(code, _)| *code == self)
+            .map(|(_, text)| *text)
+    }
+}
+
+impl fmt::Display for ErrCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", self.0)
+    }
+}
+
+macro_rules! error_codes {
+    ($($code:ident: $num:literal,)*) => {
+        $(pub const $code: ErrCode = ErrCode($num);)*
+
+        /// The error codes and their explanation.
+        pub(crate) static REGISTRY: &[(ErrCode, &str)] = &[
+            $(($code, include_str!(concat!("error_codes/", stringify!($code), ".md"))),)*
+        ];
+    };
+}
+
+error_codes! {
+    // lexer
+    E0001: 1,
+    E0002: 2,
+    E0003: 3,
+    E0004: 4,
+    E0005: 5,
+    E0006: 6,
+    E0007: 7,
+    // parser
+    E0008: 8,
+    E0009: 9,
+    E0010: 10,
+    // modules and names
+    E0011: 11,
+    E0012: 12,
+    E0013: 13,
+    E0014: 14,
+    E0015: 15,
+    E0016: 16,
+    E0017: 17,
+    E0018: 18,
+    // types
+    E0019: 19,
+    E0020: 20,
+    E0021: 21,
+    E0022: 22,
+    E0023: 23,
+    E0024: 24,
+    E0025: 25,
+    E0026: 26,
+    E0027: 27,
+    E0028: 28,
+    E0029: 29,
+    E0030: 30,
+    E0031: 31,
+    E0032: 32,
+    E0033: 33,
+    E0034: 34,
+    E0035: 35,
+    E0036: 36,
+    E0037: 37,
+}
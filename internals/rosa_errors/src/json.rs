@@ -0,0 +1,145 @@
+//! JSON output of the diagnostics, used by the tools consuming them instead
+//! of the rendered text.
+//!
+//! Each diagnostic is written as one JSON object per line, like:
+//!
+//! ```json
+//! {"level":"error","code":"E0013","message":"..","spans":[{"file":"a.ro","byte_start":4,
+//! "byte_end":7,"line_start":1,"col_start":5,"line_end":1,"col_end":8,
+//! "is_primary":true,"label":null}],"children":[{"level":"note","message":".."}],
+//! "suggestions":[{"message":"..","file":"a.ro","byte_start":4,"byte_end":7,
+//! "line_start":1,"col_start":5,"line_end":1,"col_end":8,"replacement":"foo",
+//! "applicability":"maybe-incorrect"}]}
+//! ```
+//!
+//! The byte offsets are relative to the start of the file, the lines and the
+//! columns start from one and the end column is exclusive.
+
+use std::fmt::Write as _;
+use std::io;
+
+use rosa_comm::prelude::*;
+
+use crate::{Diag, DiagCtxt};
+
+impl Diag {
+    /// Serializes the diagnostic to a JSON object, the spans are resolved with
+    /// the source map of the context.
+    pub fn to_json(&self, dcx: &DiagCtxt) -> String {
+        let spans = self
+            .span_labels()
+            .iter()
+            .filter_map(|label| span_to_json(dcx, label))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        // the helps with a suggestion are only in the suggestions.
+        let children = self
+            .children()
+            .iter()
+            .filter(|child| child.suggestion.is_none())
+            .map(|child| {
+                format!(
+                    r#"{{"level":{},"message":{}}}"#,
+                    json_str(child.level.name()),
+                    json_str(&child.msg)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let suggestions = self
+            .suggestions()
+            .filter_map(|(msg, sugg)| {
+                Some(format!(
+                    r#"{{"message":{},{},"replacement":{},"applicability":{}}}"#,
+                    json_str(msg),
+                    span_fields(dcx, &sugg.span)?,
+                    json_str(&sugg.replacement),
+                    json_str(sugg.applicability.name()),
+                ))
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            concat!(
+                r#"{{"level":{},"code":{},"message":{},"spans":[{spans}],"#,
+                r#""children":[{children}],"suggestions":[{suggestions}]}}"#
+            ),
+            json_str(self.level().name()),
+            self.code()
+                .map_or_else(|| "null".to_string(), |code| json_str(&code.to_string())),
+            json_str(self.message()),
+            spans = spans,
+            children = children,
+            suggestions = suggestions,
+        )
+    }
+}
+
+impl DiagCtxt<'_> {
+    /// Writes the diagnostics emitted as JSON, one per line, in the order of
+    /// `sorted_diags`.
+    pub fn emit_json(&self, w: &mut impl io::Write) -> io::Result<()> {
+        for diag in &self.sorted_diags() {
+            writeln!(w, "{}", diag.to_json(self))?;
+        }
+        w.flush()
+    }
+}
+
+fn span_to_json(dcx: &DiagCtxt, label: &SpanLabel) -> Option<String> {
+    Some(format!(
+        r#"{{{},"is_primary":{},"label":{}}}"#,
+        span_fields(dcx, &label.span)?,
+        label.is_primary,
+        label
+            .label
+            .as_deref()
+            .map_or_else(|| "null".to_string(), json_str),
+    ))
+}
+
+/// Returns the fields locating the span, the file and the byte, line and
+/// column ranges.
+fn span_fields(dcx: &DiagCtxt, span: &Span) -> Option<String> {
+    let file = dcx.source_map().lookup_file(span.lo)?;
+    let lo = file.line_col(span.lo);
+    let hi = file.line_col(span.hi);
+
+    Some(format!(
+        concat!(
+            r#""file":{},"byte_start":{},"byte_end":{},"#,
+            r#""line_start":{},"col_start":{},"line_end":{},"col_end":{}"#,
+        ),
+        json_str(&file.path.to_string_lossy()),
+        (span.lo - file.start_pos).0,
+        (span.hi - file.start_pos).0,
+        lo.line,
+        lo.col,
+        hi.line,
+        hi.col,
+    ))
+}
+
+/// Returns the string as a JSON string literal.
+pub fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -2,21 +2,24 @@
 
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::cmp::max;
-use std::io::{self, Write};
-use std::ops::Range;
-use std::path::Path;
+use std::io;
+use std::rc::Rc;
 
 use rosa_comm::prelude::*;
 
 use style::SetStyle;
-use termcolor::{Color, ColorSpec, StandardStream, WriteColor};
+use termcolor::{Color, ColorSpec, WriteColor};
 
+use crate::codes::ErrCode;
+use crate::snippet::Snippet;
 use crate::style::Style;
+pub mod codes;
+pub mod json;
 pub mod prelude;
+mod snippet;
 pub mod style;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Level {
     Error,
     Warning,
@@ -46,176 +49,265 @@ impl Level {
         spec
     }
 
-    pub fn format(&self, s: &mut StandardStream) -> io::Result<()> {
-        s.set_color(&self.color())?;
+    /// Returns the name of the level, as it is rendered.
+    pub fn name(&self) -> &'static str {
         match self {
-            Level::Error => write!(s, "error"),
-            Level::Warning => write!(s, "warning"),
-            Level::Note => write!(s, "note"),
-            Level::Help => write!(s, "help"),
-        }?;
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Note => "note",
+            Level::Help => "help",
+        }
+    }
+
+    pub fn format(&self, s: &mut impl WriteColor) -> io::Result<()> {
+        s.set_color(&self.color())?;
+        write!(s, "{}", self.name())?;
         s.set_no_style()?;
         Ok(())
     }
 }
 
 /// `Diag` for `Diagnostic`
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Diag {
     level: Level,
+    /// The error code, explained by `rosac --explain`.
+    code: Option<ErrCode>,
     msg: DiagMessage,
     span: MultiSpan,
+    /// The notes and helps attached to the diagnostic.
+    children: Vec<SubDiag>,
+}
+
+/// A note or a help attached to a diagnostic, rendered after its snippet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubDiag {
+    pub level: Level,
+    pub msg: DiagMessage,
+    /// The change of the code proposed by a help.
+    pub suggestion: Option<Suggestion>,
+}
+
+/// A change of the source code fixing the diagnostic, rendered as a help
+/// showing the code changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    /// The code replaced, it is empty for an insertion.
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+/// How confident we are that a suggestion is what the user meant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Applicability {
+    /// The suggestion is certainly right, it can be applied without being
+    /// reviewed, e.g: by `rosac --fix`.
+    MachineApplicable,
+    /// The suggestion may not be what the user meant.
+    MaybeIncorrect,
+}
+
+impl Applicability {
+    /// Returns the name of the applicability, as it is in the JSON output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+        }
+    }
 }
 
 impl Diag {
-    pub fn format(&self, dcx: &DiagCtxt, s: &mut StandardStream) -> io::Result<()> {
-        let prim_pos = self.primary_line_pos(dcx);
-        let LineCol { line, col } = prim_pos[0].start;
+    pub fn format(&self, dcx: &DiagCtxt, s: &mut impl WriteColor) -> io::Result<()> {
+        // the snippets of the files the spans point to, the file of the first
+        // primary span comes first.
+        let mut files: Vec<(Rc<SourceFile>, Vec<SpanLabel>)> = Vec::new();
+        let mut span_labels = self.span.span_labels();
+        span_labels.sort_by_key(|l| l.span != *self.span.primary());
+        for label in span_labels {
+            let Some(file) = dcx.source_map.lookup_file(label.span.lo) else {
+                continue;
+            };
+            match files.iter_mut().find(|(f, _)| Rc::ptr_eq(f, &file)) {
+                Some((_, labels)) => labels.push(label),
+                None => files.push((file, vec![label])),
+            }
+        }
+        let snippets = files
+            .iter()
+            .map(|(file, labels)| {
+                let mut snippet = Snippet::new(file);
+                for label in labels {
+                    snippet.add(label);
+                }
+                (file, labels[0].span.lo, snippet)
+            })
+            .collect::<Vec<_>>();
 
-        s.set_style(Style::PathLineCol, &self.level)?;
-        write!(s, "{}:{}:{}: ", dcx.filepath.display(), line, col)?;
-        s.set_no_style()?;
+        if let Some((file, lo, _)) = snippets.first() {
+            let LineCol { line, col } = file.line_col(*lo);
+            s.set_style(Style::PathLineCol, &self.level)?;
+            write!(s, "{}:{}:{}: ", file.path.display(), line, col)?;
+            s.set_no_style()?;
+        }
 
         self.level.format(s)?;
+        if let Some(code) = self.code {
+            s.set_color(&self.level.color())?;
+            write!(s, "[{code}]")?;
+            s.set_no_style()?;
+        }
         write!(s, ": ")?;
         s.set_style(Style::HeaderMsg, &self.level)?;
         write!(s, "{}", self.msg)?;
         s.set_no_style()?;
-
-        self.render_snippet(dcx, s, prim_pos)?;
         writeln!(s)?;
-        s.flush()?;
-        Ok(())
-    }
 
-    pub fn primary_line_pos(&self, dcx: &DiagCtxt) -> Vec<FullLinePos> {
-        let mut lines = Vec::new();
+        let suggestions = self
+            .suggestions()
+            .filter_map(|(msg, sugg)| Some((dcx.source_map.lookup_file(sugg.span.lo)?, msg, sugg)))
+            .collect::<Vec<_>>();
 
-        for span in self.span.primaries() {
-            let lo = dcx.line_col(span.lo);
-            let mut hi = dcx.line_col(span.hi);
-            let c = dcx.filetext.get(span.lo.0 as usize..span.hi.0 as usize);
+        let width = snippets
+            .iter()
+            .map(|(_, _, snippet)| snippet.line_no_width())
+            .chain(
+                suggestions
+                    .iter()
+                    .map(|(file, _, sugg)| file.line_col(sugg.span.lo).line.to_string().len()),
+            )
+            .max()
+            .unwrap_or(3);
 
-            // handle the case where the diag wants to point to a new line,
-            // it's kinda a hacky fix but it is what it is..
-            if let Some("\n") = c {
-                hi = LineCol {
-                    line: lo.line,
-                    col: lo.col + 1,
-                };
+        for (i, (file, lo, snippet)) in snippets.iter().enumerate() {
+            // the spans in other files than the first one are introduced with
+            // the location they point to.
+            if i != 0 {
+                let LineCol { line, col } = file.line_col(*lo);
+                s.set_style(Style::LineNumber, &self.level)?;
+                write!(s, "{:width$}--> ", "")?;
+                s.set_no_style()?;
+                writeln!(s, "{}:{}:{}", file.path.display(), line, col)?;
             }
-            lines.push(lo..hi);
+            snippet.render(s, &self.level, width)?;
         }
 
-        lines
-    }
+        for child in self.children.iter().filter(|c| c.suggestion.is_none()) {
+            s.set_style(Style::LineNumber, &self.level)?;
+            write!(s, "{:width$}= ", "")?;
+            s.set_no_style()?;
+            child.level.format(s)?;
+            writeln!(s, ": {}", child.msg)?;
+        }
 
-    /// Returns true if the diagnostic is an error.
-    pub fn is_error(&self) -> bool {
-        matches!(&self.level, Level::Error)
-    }
+        for (file, msg, sugg) in &suggestions {
+            Level::Help.format(s)?;
+            writeln!(s, ": {msg}")?;
+            snippet::render_suggestion(s, &self.level, width, file, sugg)?;
+        }
 
-    fn render_snippet(
-        &self,
-        dcx: &DiagCtxt,
-        s: &mut StandardStream,
-        prim_pos: Vec<FullLinePos>,
-    ) -> io::Result<()> {
-        // TODO: remove this unwrap and put something else.
-        let lines_data = self.build_lines_data(dcx, prim_pos).unwrap();
         writeln!(s)?;
+        s.flush()?;
+        Ok(())
+    }
 
-        let lines = lines_data.lines();
-        let mut line_no_width = 3;
+    /// Sets the error code of the diagnostic.
+    pub fn with_code(mut self, code: ErrCode) -> Diag {
+        self.code = Some(code);
+        self
+    }
 
-        for no in &lines {
-            let width = no.to_string().len();
-            line_no_width = max(width, line_no_width);
-        }
+    /// Adds a label to the span, it is a secondary span if it is not one of
+    /// the primary spans of the diagnostic.
+    pub fn with_span_label(mut self, span: Span, label: impl Into<DiagMessage>) -> Diag {
+        self.span.push_span_label(span, label.into());
+        self
+    }
 
-        let mut previous_line_no = lines[0];
-        for &line in &lines {
-            if previous_line_no + 3 <= line {
-                s.set_style(Style::LineNumber, &self.level)?;
-                writeln!(s, "...")?;
-                s.set_no_style()?;
-            }
-            self.print_line(dcx, s, line, line_no_width, lines_data.get(line))?;
-            previous_line_no = line;
-        }
+    /// Adds a label to the first primary span.
+    pub fn with_label(self, label: impl Into<DiagMessage>) -> Diag {
+        let span = self.span.primary().clone();
+        self.with_span_label(span, label)
+    }
 
-        Ok(())
+    /// Attaches a note to the diagnostic.
+    pub fn with_note(mut self, msg: impl Into<DiagMessage>) -> Diag {
+        self.children.push(SubDiag {
+            level: Level::Note,
+            msg: msg.into(),
+            suggestion: None,
+        });
+        self
     }
 
-    fn build_lines_data(&self, dcx: &DiagCtxt, prim_pos: Vec<FullLinePos>) -> Option<LinesData> {
-        let mut data = LinesData::new();
-
-        for prim in prim_pos {
-            if prim.start.line != prim.end.line {
-                // Mark the bit of code at the start
-                data.push_or_append(
-                    prim.start.line,
-                    // plus one at the end because starts from one.
-                    prim.start.col..dcx.get_line_width(prim.start.line).unwrap() as u32 + 1,
-                );
-
-                // Mark the lines in between the start and the end
-                let diff = prim.end.line - prim.start.line;
-                if diff == 2 {
-                    let l = prim.start.line + 1;
-                    // plus one at the end of the range because the range is offseted by one.
-                    data.push_or_append(l, 1..dcx.get_line_width(l)? as u32 + 1)?;
-                }
+    /// Attaches a help to the diagnostic.
+    pub fn with_help(mut self, msg: impl Into<DiagMessage>) -> Diag {
+        self.children.push(SubDiag {
+            level: Level::Help,
+            msg: msg.into(),
+            suggestion: None,
+        });
+        self
+    }
 
-                // Mark the end of the span
-                data.push_or_append(prim.end.line, 1..prim.end.col + 1)?;
-            }
-            let line = prim.start.line;
-            data.push_or_append(line, prim.start.col..prim.end.col)?;
-        }
+    /// Attaches a help suggesting to replace the code of the span.
+    pub fn with_suggestion(
+        mut self,
+        span: Span,
+        msg: impl Into<DiagMessage>,
+        replacement: impl Into<String>,
+        applicability: Applicability,
+    ) -> Diag {
+        self.children.push(SubDiag {
+            level: Level::Help,
+            msg: msg.into(),
+            suggestion: Some(Suggestion {
+                span,
+                replacement: replacement.into(),
+                applicability,
+            }),
+        });
+        self
+    }
 
-        Some(data)
+    /// Returns the message of the diagnostic.
+    pub fn message(&self) -> &str {
+        &self.msg
     }
 
-    /// When calling this function, curs is assumed to be sorted
-    fn print_line(
-        &self,
-        dcx: &DiagCtxt,
-        s: &mut StandardStream,
-        line: u32,
-        width: usize,
-        curs: Vec<Range<u32>>,
-    ) -> io::Result<()> {
-        s.set_style(Style::LineNumber, &self.level)?;
-        write!(s, "{:^width$}| ", line)?;
-        s.set_no_style()?;
-        writeln!(s, "{}", dcx.get_line(line).unwrap())?;
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
 
-        s.set_style(Style::LineNumber, &self.level)?;
-        write!(s, "{:width$}| ", "")?;
+    pub fn code(&self) -> Option<ErrCode> {
+        self.code
+    }
 
-        s.set_style(Style::Level(self.level.clone()), &self.level)?;
+    /// Returns the first primary span of the diagnostic.
+    pub fn primary_span(&self) -> &Span {
+        self.span.primary()
+    }
 
-        // Find the maximum end point among all cursors
-        let max = curs.iter().map(|r| r.end).max().unwrap_or(0);
-        let mut in_range = vec![false; max as usize];
+    /// Returns the primary spans and the labelled spans of the diagnostic.
+    pub fn span_labels(&self) -> Vec<SpanLabel> {
+        self.span.span_labels()
+    }
 
-        // Mark the indices within each Range.
-        for cur in curs {
-            for i in cur {
-                // minus one in the index because the cursors starts at 1.
-                in_range[i as usize - 1] = true;
-            }
-        }
+    pub fn children(&self) -> &[SubDiag] {
+        &self.children
+    }
 
-        for in_r in in_range {
-            write!(s, "{}", if in_r { '^' } else { ' ' })?;
-        }
+    /// Returns the suggestions of the helps, with their message.
+    pub fn suggestions(&self) -> impl Iterator<Item = (&DiagMessage, &Suggestion)> {
+        self.children
+            .iter()
+            .filter_map(|c| Some((&c.msg, c.suggestion.as_ref()?)))
+    }
 
-        s.set_no_style()?;
-        writeln!(s)?;
-        s.flush()?;
-        Ok(())
+    /// Returns true if the diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        matches!(&self.level, Level::Error)
     }
 }
 
@@ -223,21 +315,28 @@ pub type DiagMessage = Cow<'static, str>;
 
 #[derive(Debug)]
 pub struct DiagCtxt<'r> {
-    filetext: &'r str,
-    filepath: &'r Path,
+    /// The source files the spans of the diagnostics point to.
+    source_map: &'r SourceMap,
 
     diags: RefCell<Vec<Diag>>,
+    /// The maximum count of errors rendered, the other ones are only counted.
+    error_limit: Option<usize>,
 }
 
 impl<'r> DiagCtxt<'r> {
-    pub fn new(filetext: &'r str, filepath: &'r Path) -> Self {
+    pub fn new(source_map: &'r SourceMap) -> Self {
         DiagCtxt {
-            filetext,
-            filepath,
+            source_map,
             diags: RefCell::new(Vec::new()),
+            error_limit: None,
         }
     }
 
+    /// Sets the maximum count of errors rendered by `render_all`.
+    pub fn set_error_limit(&mut self, limit: usize) {
+        self.error_limit = Some(limit);
+    }
+
     pub fn diag(
         &'r self,
         level: Level,
@@ -246,8 +345,10 @@ impl<'r> DiagCtxt<'r> {
     ) -> Diag {
         Diag {
             level,
+            code: None,
             msg: msg.into(),
             span: MultiSpan::from_spans(primary_spans),
+            children: Vec::new(),
         }
     }
 
@@ -275,30 +376,62 @@ impl<'r> DiagCtxt<'r> {
         self.diag(Level::Warning, msg, primary_spans)
     }
 
-    pub fn line_col(&self, idx: BytePos) -> LineCol {
-        let mut line = 1;
-        let mut col = 1;
+    /// Returns the line and the column of the global position, in the file
+    /// it belongs to.
+    pub fn line_col(&self, pos: BytePos) -> Option<LineCol> {
+        self.source_map.lookup_line_col(pos).map(|(_, lc)| lc)
+    }
 
-        for (i, ch) in self.filetext.char_indices() {
-            if i == idx.into() {
-                break;
-            }
-            match ch {
-                '\n' => {
-                    col = 1;
-                    line += 1;
+    /// Renders the diagnostics sorted by position, up to the error limit, and
+    /// ends with the count of errors and warnings.
+    pub fn render_all(&self, s: &mut impl WriteColor) {
+        let (mut errors, mut warnings) = (0, 0);
+        for d in self.sorted_diags() {
+            match d.level {
+                Level::Error => {
+                    errors += 1;
+                    if self.error_limit.is_some_and(|limit| errors > limit) {
+                        continue;
+                    }
                 }
-                _ => col += 1,
+                Level::Warning => warnings += 1,
+                Level::Note | Level::Help => {}
+            }
+            d.format(self, s).unwrap();
+        }
+
+        if let Some(hidden) = self.error_limit.and_then(|limit| errors.checked_sub(limit)) {
+            if hidden != 0 {
+                let msg = format!("{} not shown", plural(hidden, "more error"));
+                render_summary(s, &Level::Error, &msg).unwrap();
             }
         }
 
-        LineCol { line, col }
+        if errors != 0 {
+            let mut msg = format!("aborting due to {}", plural(errors, "error"));
+            if warnings != 0 {
+                msg += &format!("; {}", plural(warnings, "warning"));
+            }
+            render_summary(s, &Level::Error, &msg).unwrap();
+        } else if warnings != 0 {
+            let msg = format!("{} emitted", plural(warnings, "warning"));
+            render_summary(s, &Level::Warning, &msg).unwrap();
+        }
     }
 
-    pub fn render_all(&self, s: &mut StandardStream) {
-        for d in self.diags.borrow().iter() {
-            d.format(self, s).unwrap();
+    /// Returns the diagnostics emitted sorted by the position of their
+    /// primary span, without the exact duplicates, e.g: an error of the lexer
+    /// emitted each time the token is lexed. The diagnostics at the same
+    /// position stay in the order they were emitted.
+    pub fn sorted_diags(&self) -> Vec<Diag> {
+        let mut diags = Vec::new();
+        for diag in self.diags.borrow().iter() {
+            if !diags.contains(diag) {
+                diags.push(diag.clone());
+            }
         }
+        diags.sort_by_key(|d| d.primary_span().lo);
+        diags
     }
 
     pub fn emit_diag(&self, diag: Diag) {
@@ -311,6 +444,59 @@ impl<'r> DiagCtxt<'r> {
         }
     }
 
+    /// Consumes the context and returns the diagnostics emitted.
+    pub fn into_diags(self) -> Vec<Diag> {
+        self.diags.into_inner()
+    }
+
+    /// Returns the new source of the files changed by the machine-applicable
+    /// suggestions of the diagnostics emitted. A suggestion overlapping one
+    /// already applied is skipped.
+    pub fn apply_suggestions(&self) -> Vec<(Rc<SourceFile>, String)> {
+        let mut files: Vec<(Rc<SourceFile>, Vec<&Suggestion>)> = Vec::new();
+        let diags = self.diags.borrow();
+        let suggestions = diags
+            .iter()
+            .flat_map(|d| d.suggestions().map(|(_, sugg)| sugg))
+            .filter(|sugg| sugg.applicability == Applicability::MachineApplicable);
+        for sugg in suggestions {
+            let Some(file) = self.source_map.lookup_file(sugg.span.lo) else {
+                continue;
+            };
+            match files.iter_mut().find(|(f, _)| Rc::ptr_eq(f, &file)) {
+                Some((_, suggs)) => suggs.push(sugg),
+                None => files.push((file, vec![sugg])),
+            }
+        }
+
+        files
+            .into_iter()
+            .map(|(file, mut suggs)| {
+                suggs.sort_by_key(|sugg| (sugg.span.lo, sugg.span.hi));
+
+                let mut src = String::with_capacity(file.src.len());
+                // offset in the file of the code not yet copied.
+                let mut copied = 0;
+                let mut last = None;
+                for sugg in suggs {
+                    let lo = usize::from(sugg.span.lo - file.start_pos);
+                    let hi = usize::from(sugg.span.hi - file.start_pos);
+                    // an overlapping suggestion, or a second one for the
+                    // same code.
+                    if lo < copied || last == Some((lo, hi)) {
+                        continue;
+                    }
+                    src.push_str(&file.src[copied..lo]);
+                    src.push_str(&sugg.replacement);
+                    copied = hi;
+                    last = Some((lo, hi));
+                }
+                src.push_str(&file.src[copied..]);
+                (file, src)
+            })
+            .collect()
+    }
+
     pub fn failed(&self) -> bool {
         for diag in self.diags.borrow().iter() {
             if diag.is_error() {
@@ -320,29 +506,28 @@ impl<'r> DiagCtxt<'r> {
         false
     }
 
-    /// Returns the content of the source file at the `line`
-    ///
-    /// The line number argument starts from one.
-    fn get_line(&self, line: u32) -> Option<&str> {
-        // NOTE: This is slow because we are creating a new iterator every time
-        // want top get the content of one line it may be faster if we store
-        // the byte offset of the start and end of each line in a vector.
-        self.filetext.lines().nth(line as usize - 1)
-    }
-
-    /// Returns the length, in bytes (not utf8 codepoints or something like
-    /// that..) of the `line` in the source file.
-    fn get_line_width(&self, line: u32) -> Option<usize> {
-        let width = self.get_line(line).map(|s| s.len());
-        width
+    pub fn source_map(&self) -> &'r SourceMap {
+        self.source_map
     }
+}
 
-    pub fn filetext(&self) -> &str {
-        self.filetext
-    }
+/// Renders a line summing up the diagnostics, without snippet.
+fn render_summary(s: &mut impl WriteColor, level: &Level, msg: &str) -> io::Result<()> {
+    level.format(s)?;
+    write!(s, ": ")?;
+    s.set_style(Style::HeaderMsg, level)?;
+    write!(s, "{msg}")?;
+    s.set_no_style()?;
+    writeln!(s)?;
+    s.flush()
+}
 
-    pub fn filepath(&self) -> &Path {
-        self.filepath
+/// Returns the count followed by the noun, in the plural if needed.
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
     }
 }
 
@@ -394,3 +579,502 @@ impl<T, E, Es> Fuzzy<T, E, Es> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use termcolor::Buffer;
+
+    use super::*;
+
+    /// Renders the diagnostic created with the context of a source map
+    /// containing the files, without colors.
+    fn render(
+        files: &[(&str, &str)],
+        f: impl FnOnce(&DiagCtxt, &[Rc<SourceFile>]) -> Diag,
+    ) -> String {
+        let sm = SourceMap::new();
+        let files = files
+            .iter()
+            .map(|(path, src)| sm.add_file(*path, *src))
+            .collect::<Vec<_>>();
+        let dcx = DiagCtxt::new(&sm);
+
+        let mut buf = Buffer::no_color();
+        f(&dcx, &files).format(&dcx, &mut buf).unwrap();
+        String::from_utf8(buf.into_inner()).unwrap()
+    }
+
+    /// Returns the span of the `n`th occurrence, from zero, of `pat` in the
+    /// file.
+    fn find(file: &SourceFile, pat: &str, n: usize) -> Span {
+        let (i, _) = file.src.match_indices(pat).nth(n).unwrap();
+        Span::new(i, i + pat.len()).offset(file.start_pos)
+    }
+
+    #[test]
+    fn render_primary_span() {
+        let out = render(&[("a.ro", "fun main() =\n    foo(1)\n")], |dcx, f| {
+            dcx.struct_err("cannot find value 'foo'", find(&f[0], "foo", 0))
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:2:5: error: cannot find value 'foo'",
+                " 2 |     foo(1)",
+                "   |     ^^^",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_all_sorted_and_limited() {
+        let sm = SourceMap::new();
+        let file = sm.add_file("a.ro", "fun main() =\n    foo(bar, baz)\n");
+        let mut dcx = DiagCtxt::new(&sm);
+        dcx.set_error_limit(2);
+
+        let err = |name: &str| {
+            dcx.struct_err(format!("cannot find value '{name}'"), find(&file, name, 0))
+        };
+        dcx.emit_diag(err("baz"));
+        dcx.emit_diag(err("foo"));
+        dcx.emit_diag(dcx.struct_warn("unused function", find(&file, "main", 0)));
+        dcx.emit_diag(err("foo"));
+        dcx.emit_diag(err("bar"));
+
+        let mut buf = Buffer::no_color();
+        dcx.render_all(&mut buf);
+        let out = String::from_utf8(buf.into_inner()).unwrap();
+        let headers = out
+            .lines()
+            .filter(|l| !l.starts_with(' ') && !l.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headers,
+            [
+                "a.ro:1:5: warning: unused function",
+                "a.ro:2:5: error: cannot find value 'foo'",
+                "a.ro:2:9: error: cannot find value 'bar'",
+                "error: 1 more error not shown",
+                "error: aborting due to 3 errors; 1 warning",
+            ]
+        );
+    }
+
+    #[test]
+    fn render_error_code() {
+        let out = render(&[("a.ro", "fun main() =\n    foo(1)\n")], |dcx, f| {
+            dcx.struct_err("cannot find value 'foo'", find(&f[0], "foo", 0))
+                .with_code(codes::E0013)
+        });
+        assert!(
+            out.starts_with("a.ro:2:5: error[E0013]: cannot find value 'foo'\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn render_suggestions() {
+        let src = "fun foo(count: int) =\n    if cont > 1\n        1\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            let pred = find(&f[0], "1\n", 0);
+            dcx.struct_err("expected :, found new line", find(&f[0], "\n", 1))
+                .with_suggestion(
+                    Span::from_inner(pred.lo + BytePos(1), pred.lo + BytePos(1)),
+                    "add `:` after the predicate",
+                    ":",
+                    Applicability::MachineApplicable,
+                )
+                .with_suggestion(
+                    find(&f[0], "cont ", 0),
+                    "a value with a similar name exists",
+                    "count ",
+                    Applicability::MaybeIncorrect,
+                )
+                .with_suggestion(
+                    find(&f[0], " > 1", 0),
+                    "remove the comparison",
+                    "",
+                    Applicability::MaybeIncorrect,
+                )
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:2:16: error: expected :, found new line",
+                " 2 |     if cont > 1",
+                "   |                ^",
+                "help: add `:` after the predicate",
+                " 2 |     if cont > 1:",
+                "   |                +",
+                "help: a value with a similar name exists",
+                " 2 |     if count > 1",
+                "   |        ~~~~~~",
+                "help: remove the comparison",
+                " 2 |     if cont > 1",
+                "   |            ----",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_tabs() {
+        let out = render(
+            &[("a.ro", "fun main() =\n\tif\tcont:\n\t\tfoo()\n")],
+            |dcx, f| {
+                dcx.struct_err("cannot find value 'cont'", find(&f[0], "cont", 0))
+                    .with_span_label(find(&f[0], "foo", 0), "called here")
+                    .with_suggestion(
+                        find(&f[0], "cont", 0),
+                        "a value with a similar name exists",
+                        "count",
+                        Applicability::MaybeIncorrect,
+                    )
+            },
+        );
+        assert_eq!(
+            out,
+            [
+                "a.ro:2:5: error: cannot find value 'cont'",
+                " 2 |     if    cont:",
+                "   |           ^^^^",
+                " 3 |         foo()",
+                "   |         --- called here",
+                "help: a value with a similar name exists",
+                " 2 |     if    count:",
+                "   |           ~~~~~",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_emoji() {
+        let out = render(&[("a.ro", "let s = \"🌹🌹\" + rose\n")], |dcx, f| {
+            dcx.struct_err("cannot find value 'rose'", find(&f[0], "rose", 0))
+                .with_span_label(find(&f[0], "🌹", 1), "a rose")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:1:16: error: cannot find value 'rose'",
+                " 1 | let s = \"🌹🌹\" + rose",
+                "   |            --    ^^^^",
+                "   |            |",
+                "   |            a rose",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_cjk() {
+        let out = render(&[("a.ro", "let 名前 = 名 + 1\n")], |dcx, f| {
+            // the span of `名` alone, its bytes are followed by a space.
+            let lo = find(&f[0], "名 ", 0).lo;
+            let name = Span::from_inner(lo, lo + "名".len().into());
+            dcx.struct_err("cannot find value '名'", name.clone())
+                .with_suggestion(
+                    name,
+                    "a value with a similar name exists",
+                    "名前",
+                    Applicability::MaybeIncorrect,
+                )
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:1:10: error: cannot find value '名'",
+                " 1 | let 名前 = 名 + 1",
+                "   |            ^^",
+                "help: a value with a similar name exists",
+                " 1 | let 名前 = 名前 + 1",
+                "   |            ~~~~",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_multiline_span_two_lines() {
+        let src = "fun main() =\n    let x = foo(1,\n        2)\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            let lo = find(&f[0], "foo", 0).lo;
+            let hi = find(&f[0], "2)", 0).hi;
+            dcx.struct_err("cannot find function 'foo'", Span::from_inner(lo, hi))
+                .with_label("not found in this scope")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:2:13: error: cannot find function 'foo'",
+                " 2 |       let x = foo(1,",
+                "   |  _____________^",
+                " 3 | |         2)",
+                "   | |__________^ not found in this scope",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_multiline_span_three_lines() {
+        let src = "fun main() =\n    if true:\n        foo()\n    bar()\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            let lo = find(&f[0], "if", 0).lo;
+            let hi = find(&f[0], "bar()", 0).hi;
+            dcx.struct_err("mismatched types", Span::from_inner(lo, hi))
+                .with_label("expected int, found void")
+                .with_span_label(find(&f[0], "main", 0), "expected because of this")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:2:5: error: mismatched types",
+                " 1 |   fun main() =",
+                "   |       ---- expected because of this",
+                " 2 |       if true:",
+                "   |  _____^",
+                " 3 | |         foo()",
+                " 4 | |     bar()",
+                "   | |_________^ expected int, found void",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_multiline_span_many_lines() {
+        let src = "fun main() =\n    let x = [\n        1,\n        2,\n        3,\n        4,\n        5,\n    ]\n    x\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            let lo = find(&f[0], "[", 0).lo;
+            let hi = find(&f[0], "]", 0).hi;
+            dcx.struct_err("mismatched types", Span::from_inner(lo, hi))
+                .with_label("expected int, found [int]")
+                .with_span_label(find(&f[0], "x", 0), "declared here")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:2:13: error: mismatched types",
+                " 2 |       let x = [",
+                "   |           - declared here",
+                "   |  _____________^",
+                " 3 | |         1,",
+                "...",
+                " 7 | |         5,",
+                " 8 | |     ]",
+                "   | |_____^ expected int, found [int]",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn apply_suggestions() {
+        let sm = SourceMap::new();
+        let a = sm.add_file("a.ro", "if a\n    b\nelse\n    c\n");
+        let b = sm.add_file("b.ro", "fun main() =\n    prnt(1)\n");
+        let dcx = DiagCtxt::new(&sm);
+        let insert = |file: &SourceFile, pat: &str| {
+            let span = find(file, pat, 0);
+            Span::from_inner(span.hi, span.hi)
+        };
+        let colon = |span: Span| {
+            dcx.struct_err("expected :", span.clone()).with_suggestion(
+                span,
+                "add `:`",
+                ":",
+                Applicability::MachineApplicable,
+            )
+        };
+        // the second suggestion of the same insertion is skipped.
+        dcx.emit_diag(colon(insert(&a, "else")));
+        dcx.emit_diag(colon(insert(&a, "if a")));
+        dcx.emit_diag(colon(insert(&a, "if a")));
+        dcx.emit_diag(
+            dcx.struct_err("cannot find value", find(&b, "prnt", 0))
+                .with_suggestion(
+                    find(&b, "prnt", 0),
+                    "a similar name",
+                    "print",
+                    Applicability::MaybeIncorrect,
+                ),
+        );
+
+        let fixed = dcx.apply_suggestions();
+        assert_eq!(fixed.len(), 1);
+        assert!(Rc::ptr_eq(&fixed[0].0, &a));
+        assert_eq!(fixed[0].1, "if a:\n    b\nelse:\n    c\n");
+    }
+
+    #[test]
+    fn error_codes_registry() {
+        for (i, (code, text)) in codes::REGISTRY.iter().enumerate() {
+            assert_eq!(code.to_string(), format!("E{:04}", i + 1));
+            assert_eq!(codes::ErrCode::parse(&code.to_string()), Some(*code));
+            assert!(text.starts_with(char::is_uppercase), "{code}");
+        }
+        assert_eq!(codes::ErrCode::parse("0019"), Some(codes::E0019));
+        assert_eq!(codes::ErrCode::parse("E19"), None);
+        assert_eq!(codes::ErrCode::parse("E9999").unwrap().explanation(), None);
+    }
+
+    #[test]
+    fn render_labels_and_children() {
+        let src = "fun foo() =\n    1\n\nfun foo() =\n    2\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            dcx.struct_err(
+                "the symbol 'foo' is defined multiple times",
+                find(&f[0], "foo", 1),
+            )
+            .with_label("`foo` redefined here")
+            .with_span_label(find(&f[0], "foo", 0), "previous definition of `foo` here")
+            .with_note("a note")
+            .with_help("a help")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:4:5: error: the symbol 'foo' is defined multiple times",
+                " 1 | fun foo() =",
+                "   |     --- previous definition of `foo` here",
+                "...",
+                " 4 | fun foo() =",
+                "   |     ^^^ `foo` redefined here",
+                "   = note: a note",
+                "   = help: a help",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_single_skipped_line() {
+        let src = "fun foo() =\n    1\nfun foo() =\n    2\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            dcx.struct_err(
+                "the symbol 'foo' is defined multiple times",
+                find(&f[0], "foo", 1),
+            )
+            .with_span_label(find(&f[0], "foo", 0), "previous definition of `foo` here")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:3:5: error: the symbol 'foo' is defined multiple times",
+                " 1 | fun foo() =",
+                "   |     --- previous definition of `foo` here",
+                " 2 |     1",
+                " 3 | fun foo() =",
+                "   |     ^^^",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn render_labels_on_one_line() {
+        let src = "let x: int = true\n";
+        let out = render(&[("a.ro", src)], |dcx, f| {
+            dcx.struct_err("mismatched types", find(&f[0], "true", 0))
+                .with_label("expected `int`, found `bool`")
+                .with_span_label(find(&f[0], "int", 0), "expected due to this")
+                .with_span_label(find(&f[0], "x", 0), "the variable")
+        });
+        assert_eq!(
+            out,
+            [
+                "a.ro:1:14: error: mismatched types",
+                " 1 | let x: int = true",
+                "   |     -  ---   ^^^^ expected `int`, found `bool`",
+                "   |     |  |",
+                "   |     |  expected due to this",
+                "   |     the variable",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn json_output() {
+        let sm = SourceMap::new();
+        let file = sm.add_file("dir/a.ro", "fun main() =\n    \"🌹\" + foo\n");
+        let dcx = DiagCtxt::new(&sm);
+        dcx.emit_diag(
+            dcx.struct_err("cannot find value 'foo'", find(&file, "foo", 0))
+                .with_code(codes::E0013)
+                .with_label("not \"found\"")
+                .with_span_label(find(&file, "main", 0), "in this function")
+                .with_help("a help"),
+        );
+
+        let mut out = Vec::new();
+        dcx.emit_json(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"level":"error","code":"E0013","message":"cannot find value 'foo'","spans":["#,
+                r#"{"file":"dir/a.ro","byte_start":26,"byte_end":29,"line_start":2,"col_start":11,"#,
+                r#""line_end":2,"col_end":14,"is_primary":true,"label":"not \"found\""},"#,
+                r#"{"file":"dir/a.ro","byte_start":4,"byte_end":8,"line_start":1,"col_start":5,"#,
+                r#""line_end":1,"col_end":9,"is_primary":false,"label":"in this function"}],"#,
+                r#""children":[{"level":"help","message":"a help"}],"suggestions":[]}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn render_spans_in_several_files() {
+        let out = render(
+            &[
+                ("main.ro", "import a\nfun main() =\n    secret()\n"),
+                ("a.ro", "fun secret() =\n    1\n"),
+            ],
+            |dcx, f| {
+                dcx.struct_err("`secret` is private", find(&f[0], "secret", 0))
+                    .with_label("private item")
+                    .with_span_label(find(&f[1], "secret", 0), "declared here")
+            },
+        );
+        assert_eq!(
+            out,
+            [
+                "main.ro:3:5: error: `secret` is private",
+                " 3 |     secret()",
+                "   |     ^^^^^^ private item",
+                "   --> a.ro:1:5",
+                " 1 | fun secret() =",
+                "   |     ------ declared here",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
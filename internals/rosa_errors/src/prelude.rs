@@ -1,4 +1,5 @@
 //! Prelude of the error system used to reduce the lines due to 'use' items and
 //! make it cleaner.
 
-pub use crate::{Diag, DiagCtxt, Fuzzy};
+pub use crate::codes::*;
+pub use crate::{Applicability, Diag, DiagCtxt, Fuzzy};
@@ -0,0 +1,532 @@
+//! Rendering of the snippets of source code under the header of the
+//! diagnostics, with the spans underlined and labelled.
+//!
+//! The spans are located by columns counted in chars, while the underlines
+//! are drawn in display columns: a tab is rendered as spaces and a wide char,
+//! like an emoji or a CJK char, takes two columns, see [`DisplayLine`].
+
+use std::collections::BTreeMap;
+use std::io;
+
+use rosa_comm::prelude::*;
+use termcolor::WriteColor;
+use unicode_width::UnicodeWidthChar;
+
+use crate::style::{SetStyle, Style};
+use crate::{Level, Suggestion};
+
+/// A part of a line underlined in a snippet.
+#[derive(Clone, Debug)]
+pub(crate) struct Annotation {
+    /// Column of the first character underlined, starting from one.
+    pub start: u32,
+    /// Column after the last character underlined.
+    pub end: u32,
+    pub is_primary: bool,
+    pub label: Option<String>,
+}
+
+impl Annotation {
+    fn underline_style(&self) -> Style {
+        if self.is_primary {
+            Style::UnderlinePrimary
+        } else {
+            Style::UnderlineSecondary
+        }
+    }
+
+    fn label_style(&self) -> Style {
+        if self.is_primary {
+            Style::LabelPrimary
+        } else {
+            Style::LabelSecondary
+        }
+    }
+
+    fn underline_char(&self) -> char {
+        if self.is_primary {
+            '^'
+        } else {
+            '-'
+        }
+    }
+}
+
+/// Count of spaces a tab is rendered with, a tab is always as wide so that
+/// the underlines are aligned whatever the tab stops of the terminal.
+const TAB_WIDTH: usize = 4;
+
+/// A line of source code as it is rendered, with the tabs expanded to spaces
+/// and the display column of each of its chars.
+pub(crate) struct DisplayLine {
+    text: String,
+    /// Display offset of each char, from zero, followed by the width of the
+    /// line.
+    offsets: Vec<usize>,
+}
+
+impl DisplayLine {
+    pub fn new(line: &str) -> DisplayLine {
+        let mut text = String::with_capacity(line.len());
+        let mut offsets = Vec::with_capacity(line.len() + 1);
+        let mut width = 0;
+        for c in line.chars() {
+            offsets.push(width);
+            if c == '\t' {
+                text.extend([' '; TAB_WIDTH]);
+                width += TAB_WIDTH;
+            } else {
+                text.push(c);
+                width += c.width().unwrap_or(0);
+            }
+        }
+        offsets.push(width);
+
+        DisplayLine { text, offsets }
+    }
+
+    /// Returns the display column of the char at the column `col`, both start
+    /// from one. The columns past the end of the line are one column wide.
+    pub fn col(&self, col: u32) -> u32 {
+        let idx = col as usize - 1;
+        let offset = match self.offsets.get(idx) {
+            Some(&offset) => offset,
+            None => self.offsets.last().unwrap() + idx + 1 - self.offsets.len(),
+        };
+        offset as u32 + 1
+    }
+
+    /// Returns the annotation with its columns in display columns, it stays
+    /// at least one column wide, e.g: over a zero width char.
+    fn annotation(&self, a: &Annotation) -> Annotation {
+        let start = self.col(a.start);
+        Annotation {
+            start,
+            end: self.col(a.end).max(start + 1),
+            ..a.clone()
+        }
+    }
+}
+
+/// A span over several lines, drawn with a vertical line in the margin of
+/// the snippet joining the marks of its start and of its end.
+#[derive(Clone, Debug)]
+pub(crate) struct MultilineAnnotation {
+    /// Column of the margin of the vertical line, from zero.
+    pub depth: usize,
+    /// Position of the first char of the span.
+    pub start: LineCol,
+    /// Position of the last char of the span.
+    pub end: LineCol,
+    pub is_primary: bool,
+    pub label: Option<String>,
+}
+
+impl MultilineAnnotation {
+    fn style(&self) -> Style {
+        if self.is_primary {
+            Style::UnderlinePrimary
+        } else {
+            Style::UnderlineSecondary
+        }
+    }
+
+    /// Returns true if the vertical line goes through the line.
+    fn crosses(&self, line: u32) -> bool {
+        self.start.line < line && line < self.end.line
+    }
+}
+
+/// Count of lines up to which a multiline span is rendered entirely, only the
+/// two first and two last lines of a longer span are rendered.
+const MULTILINE_MAX_LINES: u32 = 5;
+
+/// The annotated lines of a source file.
+pub(crate) struct Snippet<'f> {
+    file: &'f SourceFile,
+    /// Annotations by line number, the lines rendered.
+    lines: BTreeMap<u32, Vec<Annotation>>,
+    multiline: Vec<MultilineAnnotation>,
+}
+
+impl<'f> Snippet<'f> {
+    pub fn new(file: &'f SourceFile) -> Snippet<'f> {
+        Snippet {
+            file,
+            lines: BTreeMap::new(),
+            multiline: Vec::new(),
+        }
+    }
+
+    /// Annotates the lines of the span, the label of a multiline span is put
+    /// on its last line.
+    pub fn add(&mut self, span: &SpanLabel) {
+        let lo = self.file.line_col(span.span.lo);
+        let mut hi = self.file.line_col(span.span.hi);
+
+        // an empty span or a span of a new line still underline one char.
+        if (lo.line != hi.line && self.is_newline(&span.span)) || lo == hi {
+            hi = LineCol {
+                line: lo.line,
+                col: lo.col + 1,
+            };
+        }
+
+        // a span ending at the start of a line ends with the new line of the
+        // previous one.
+        if hi.line > lo.line && hi.col == 1 {
+            let line = hi.line - 1;
+            hi = LineCol {
+                line,
+                col: self.file.line_width(line).unwrap_or(0) as u32 + 2,
+            };
+        }
+
+        if lo.line == hi.line {
+            self.lines.entry(lo.line).or_default().push(Annotation {
+                start: lo.col,
+                end: hi.col,
+                is_primary: span.is_primary,
+                label: span.label.clone(),
+            });
+            return;
+        }
+
+        let shown = if hi.line - lo.line < MULTILINE_MAX_LINES {
+            (lo.line..=hi.line).collect::<Vec<_>>()
+        } else {
+            vec![lo.line, lo.line + 1, hi.line - 1, hi.line]
+        };
+        for line in shown {
+            self.lines.entry(line).or_default();
+        }
+
+        self.multiline.push(MultilineAnnotation {
+            depth: self.multiline.len(),
+            start: lo,
+            end: LineCol {
+                line: hi.line,
+                col: hi.col - 1,
+            },
+            is_primary: span.is_primary,
+            label: span.label.clone(),
+        });
+    }
+
+    fn is_newline(&self, span: &Span) -> bool {
+        let lo = usize::from(span.lo - self.file.start_pos);
+        let hi = usize::from(span.hi - self.file.start_pos);
+        self.file.src.get(lo..hi) == Some("\n")
+    }
+
+    /// Width of the gutter, the column of the line numbers.
+    pub fn line_no_width(&self) -> usize {
+        let last = self.lines.keys().last().copied().unwrap_or(0);
+        last.to_string().len().max(3)
+    }
+
+    pub fn render(&self, s: &mut impl WriteColor, level: &Level, width: usize) -> io::Result<()> {
+        let mut previous_line_no = None;
+        for (&line, annotations) in &self.lines {
+            match previous_line_no {
+                // a single skipped line takes the room of the `...`, it's
+                // rendered instead.
+                Some(prev) if prev + 2 == line => {
+                    self.render_line(s, level, width, prev + 1)?;
+                }
+                Some(prev) if prev + 2 < line => {
+                    s.set_style(Style::LineNumber, level)?;
+                    writeln!(s, "...")?;
+                    s.set_no_style()?;
+                }
+                _ => {}
+            }
+
+            let display = self.render_line(s, level, width, line)?;
+
+            // the ends of the multiline spans come first, then the
+            // annotations of the line and the starts of the multiline spans.
+            let ends = self.multiline.iter().filter(|m| m.end.line == line);
+            for m in ends {
+                let margin = self.margin(|other| {
+                    other.crosses(line) || (other.end.line == line && other.depth > m.depth)
+                });
+                let col = display.col(m.end.col) as usize;
+                self.render_multiline_mark(s, level, width, margin, m, col, true)?;
+            }
+
+            if !annotations.is_empty() {
+                let annotations = annotations
+                    .iter()
+                    .map(|a| display.annotation(a))
+                    .collect::<Vec<_>>();
+                let margin = self.margin(|m| m.crosses(line));
+                render_annotations(s, level, width, &margin, &annotations)?;
+            }
+
+            let starts = self.multiline.iter().filter(|m| m.start.line == line);
+            for m in starts {
+                let margin = self.margin(|other| {
+                    other.crosses(line) || (other.start.line == line && other.depth < m.depth)
+                });
+                let col = display.col(m.start.col) as usize;
+                self.render_multiline_mark(s, level, width, margin, m, col, false)?;
+            }
+
+            previous_line_no = Some(line);
+        }
+
+        Ok(())
+    }
+
+    /// Renders the line of the file with its number and the margin of the
+    /// multiline spans, and returns it as displayed.
+    fn render_line(
+        &self,
+        s: &mut impl WriteColor,
+        level: &Level,
+        width: usize,
+        line: u32,
+    ) -> io::Result<DisplayLine> {
+        let display = DisplayLine::new(self.file.line(line).unwrap_or_default());
+        s.set_style(Style::LineNumber, level)?;
+        write!(s, "{:^width$}| ", line)?;
+        s.set_no_style()?;
+        let margin = self.margin(|m| m.start.line < line && line <= m.end.line);
+        write_margin(s, level, &margin)?;
+        writeln!(s, "{}", display.text)?;
+        Ok(display)
+    }
+
+    /// Returns the cells of the margin of the multiline spans, with the
+    /// vertical line of the spans for which `is_drawn` is true.
+    fn margin(
+        &self,
+        is_drawn: impl Fn(&MultilineAnnotation) -> bool,
+    ) -> Vec<Option<&MultilineAnnotation>> {
+        self.multiline
+            .iter()
+            .map(|m| Some(m).filter(|m| is_drawn(m)))
+            .collect()
+    }
+
+    /// Renders the mark of the start or of the end of a multiline span, a
+    /// horizontal line from its vertical line to the char at the display
+    /// column `col`. The label is put after the mark of the end.
+    #[allow(clippy::too_many_arguments)]
+    fn render_multiline_mark<'a>(
+        &self,
+        s: &mut impl WriteColor,
+        level: &Level,
+        width: usize,
+        mut margin: Vec<Option<&'a MultilineAnnotation>>,
+        m: &'a MultilineAnnotation,
+        col: usize,
+        is_end: bool,
+    ) -> io::Result<()> {
+        s.set_style(Style::LineNumber, level)?;
+        write!(s, "{:width$}| ", "")?;
+        s.set_no_style()?;
+
+        // the vertical line of the span stops at the mark of its end.
+        margin[m.depth] = if is_end { Some(m) } else { None };
+        write_margin_cells(s, level, &margin[..m.depth + 1])?;
+
+        let underline = if m.is_primary { '^' } else { '-' };
+        s.set_style(m.style(), level)?;
+        let line = "_".repeat(margin.len() - m.depth + col - 1);
+        write!(s, "{line}{underline}")?;
+        if let (true, Some(label)) = (is_end, &m.label) {
+            write!(s, " {label}")?;
+        }
+        s.set_no_style()?;
+        writeln!(s)
+    }
+}
+
+/// Writes the cells of the margin of the multiline spans, followed by a
+/// space separating it from the code if it isn't empty.
+fn write_margin(
+    s: &mut (impl WriteColor + ?Sized),
+    level: &Level,
+    margin: &[Option<&MultilineAnnotation>],
+) -> io::Result<()> {
+    write_margin_cells(s, level, margin)?;
+    if !margin.is_empty() {
+        write!(s, " ")?;
+    }
+    Ok(())
+}
+
+fn write_margin_cells(
+    s: &mut (impl WriteColor + ?Sized),
+    level: &Level,
+    margin: &[Option<&MultilineAnnotation>],
+) -> io::Result<()> {
+    for cell in margin {
+        match cell {
+            Some(m) => {
+                s.set_style(m.style(), level)?;
+                write!(s, "|")?;
+                s.set_no_style()?;
+            }
+            None => write!(s, " ")?,
+        }
+    }
+    Ok(())
+}
+
+/// Renders the line of the suggestion with its replacement applied, the code
+/// inserted is marked with `+` and the code replaced with `~`, a deletion is
+/// shown on the original line marked with `-`. Only the suggestions within a
+/// line are rendered.
+pub(crate) fn render_suggestion(
+    s: &mut impl WriteColor,
+    level: &Level,
+    width: usize,
+    file: &SourceFile,
+    sugg: &Suggestion,
+) -> io::Result<()> {
+    let lo = file.line_col(sugg.span.lo);
+    let hi = file.line_col(sugg.span.hi);
+    if lo.line != hi.line || sugg.replacement.contains('\n') {
+        return Ok(());
+    }
+    let Some(line) = file.line(lo.line) else {
+        return Ok(());
+    };
+
+    let start = (lo.col - 1) as usize;
+    let end = (hi.col - 1) as usize;
+    let (text, marker, len) = if sugg.replacement.is_empty() {
+        (line.to_string(), '-', end - start)
+    } else {
+        let mut text = line.chars().take(start).collect::<String>();
+        text.push_str(&sugg.replacement);
+        text.extend(line.chars().skip(end));
+        let marker = if start == end { '+' } else { '~' };
+        (text, marker, sugg.replacement.chars().count())
+    };
+
+    // the marker is drawn under the chars `start..start + len` of the text.
+    let display = DisplayLine::new(&text);
+    let marker_start = display.col(start as u32 + 1);
+    let marker_len = (display.col((start + len) as u32 + 1) - marker_start) as usize;
+    let marker_start = marker_start as usize - 1;
+
+    s.set_style(Style::LineNumber, level)?;
+    write!(s, "{:^width$}| ", lo.line)?;
+    s.set_no_style()?;
+    writeln!(s, "{}", display.text)?;
+
+    s.set_style(Style::LineNumber, level)?;
+    write!(s, "{:width$}| ", "")?;
+    s.set_no_style()?;
+    write!(s, "{:marker_start$}", "")?;
+    s.set_color(&Level::Help.color())?;
+    write!(s, "{}", marker.to_string().repeat(marker_len))?;
+    s.set_no_style()?;
+    writeln!(s)
+}
+
+/// Renders the underlines of the annotations of a line and their labels, the
+/// label of the rightmost annotation is put after the underlines and the
+/// other ones are put below, connected to their underline.
+fn render_annotations(
+    s: &mut impl WriteColor,
+    level: &Level,
+    width: usize,
+    margin: &[Option<&MultilineAnnotation>],
+    annotations: &[Annotation],
+) -> io::Result<()> {
+    let mut annotations = annotations.to_vec();
+    annotations.sort_by_key(|a| (a.start, a.end));
+
+    let gutter = |s: &mut dyn WriteColor| -> io::Result<()> {
+        s.set_style(Style::LineNumber, level)?;
+        write!(s, "{:width$}| ", "")?;
+        s.set_no_style()?;
+        write_margin(s, level, margin)
+    };
+
+    // the underlines, the primary ones are drawn over the secondary ones.
+    let max_end = annotations.iter().map(|a| a.end).max().unwrap_or(1);
+    let mut cols: Vec<Option<&Annotation>> = vec![None; max_end as usize - 1];
+    for a in annotations.iter().filter(|a| !a.is_primary) {
+        for col in a.start..a.end {
+            cols[col as usize - 1] = Some(a);
+        }
+    }
+    for a in annotations.iter().filter(|a| a.is_primary) {
+        for col in a.start..a.end {
+            cols[col as usize - 1] = Some(a);
+        }
+    }
+
+    gutter(s)?;
+    for col in &cols {
+        match col {
+            Some(a) => {
+                s.set_style(a.underline_style(), level)?;
+                write!(s, "{}", a.underline_char())?;
+            }
+            None => {
+                s.set_no_style()?;
+                write!(s, " ")?;
+            }
+        }
+    }
+
+    let mut labelled = annotations
+        .iter()
+        .filter(|a| a.label.is_some())
+        .collect::<Vec<_>>();
+
+    // the label of the annotation ending last is put after the underlines
+    if let Some(last) = labelled.last().filter(|a| a.end == max_end) {
+        s.set_style(last.label_style(), level)?;
+        write!(s, " {}", last.label.as_ref().unwrap())?;
+        labelled.pop();
+    }
+    s.set_no_style()?;
+    writeln!(s)?;
+
+    if labelled.is_empty() {
+        return Ok(());
+    }
+
+    // the other labels are put below, from right to left, connected to the
+    // start of their underline.
+    let pipes = |s: &mut dyn WriteColor, annotations: &[&Annotation]| -> io::Result<u32> {
+        let mut col = 1;
+        for a in annotations {
+            // only one pipe is drawn for the labels starting at the same column
+            if a.start < col {
+                continue;
+            }
+            write!(s, "{:1$}", "", (a.start - col) as usize)?;
+            s.set_style(a.label_style(), level)?;
+            write!(s, "|")?;
+            s.set_no_style()?;
+            col = a.start + 1;
+        }
+        Ok(col)
+    };
+
+    gutter(s)?;
+    pipes(s, &labelled)?;
+    writeln!(s)?;
+
+    for i in (0..labelled.len()).rev() {
+        let a = labelled[i];
+        gutter(s)?;
+        let col = pipes(s, &labelled[..i])?;
+        write!(s, "{:1$}", "", a.start.saturating_sub(col) as usize)?;
+        s.set_style(a.label_style(), level)?;
+        write!(s, "{}", a.label.as_ref().unwrap())?;
+        s.set_no_style()?;
+        writeln!(s)?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::io;
 
 use crate::Level;
-use termcolor::{Color, ColorSpec, StandardStream, WriteColor};
+use termcolor::{Color, ColorSpec, WriteColor};
 
 pub enum Style {
     HeaderMsg,
@@ -53,7 +53,7 @@ pub(crate) trait SetStyle {
     fn set_no_style(&mut self) -> io::Result<()>;
 }
 
-impl SetStyle for StandardStream {
+impl<W: WriteColor + ?Sized> SetStyle for W {
     fn set_style(&mut self, style: Style, lvl: &Level) -> io::Result<()> {
         self.set_color(&style.color_spec(lvl.clone()))
     }
@@ -25,7 +25,7 @@ pub trait AbsLexer {
     fn finished(&self) -> bool;
 
     /// Get the Diag Context.
-    fn dcx(&self) -> &DiagCtxt;
+    fn dcx(&self) -> &DiagCtxt<'_>;
 }
 
 impl AbsLexer for Lexer<'_> {
@@ -51,10 +51,10 @@ impl AbsLexer for Lexer<'_> {
     }
 
     fn finished(&self) -> bool {
-        self.idx > self.file.filetext.len().into()
+        self.idx > self.start_pos + self.file.filetext.len().into()
     }
 
-    fn dcx(&self) -> &DiagCtxt {
+    fn dcx(&self) -> &DiagCtxt<'_> {
         self.dcx
     }
 }
@@ -164,7 +164,7 @@ impl<'r> AbsLexer for BufferedLexer<'r> {
         self.inner.finished() && self.buf.is_empty()
     }
 
-    fn dcx(&self) -> &DiagCtxt {
+    fn dcx(&self) -> &DiagCtxt<'_> {
         self.inner.dcx()
     }
 }
@@ -96,25 +96,31 @@ impl<'r> LexrFile<'r> {
 
 pub struct Lexer<'r> {
     file: LexrFile<'r>,
+    /// Global position of the start of the file in the source map, the
+    /// indices below start from it.
+    start_pos: BytePos,
     prev_idx: BytePos,
     idx: BytePos,
     dcx: &'r DiagCtxt<'r>,
 }
 
 impl<'r> Lexer<'r> {
-    pub fn new(filepath: &'r Path, filetext: &'r str, dcx: &'r DiagCtxt<'r>) -> Lexer<'r> {
+    pub fn new(file: &'r SourceFile, dcx: &'r DiagCtxt<'r>) -> Lexer<'r> {
         Lexer {
-            file: LexrFile::new(filepath, filetext),
-            prev_idx: 0.into(),
-            idx: 0.into(),
+            file: LexrFile::new(&file.path, &file.src),
+            start_pos: file.start_pos,
+            prev_idx: file.start_pos,
+            idx: file.start_pos,
             dcx,
         }
     }
 
-    /// Advance the iterator and the index (self.idx)
+    /// Advance the iterator and the index (self.idx) by the length in bytes
+    /// of the char.
     pub fn pop(&mut self) -> Option<char> {
-        self.idx += 1;
-        self.file.pop()
+        let c = self.file.pop();
+        self.idx += c.map_or(1, |c| c.len_utf8() as u32);
+        c
     }
 
     pub fn expect(&mut self, expected: char) {
@@ -144,9 +150,8 @@ impl<'r> Lexer<'r> {
     }
 
     pub fn window(&self, size: usize) -> Option<&str> {
-        self.file
-            .filetext
-            .get(self.prev_idx.0 as usize..self.prev_idx.0 as usize + size)
+        let start: usize = (self.prev_idx - self.start_pos).into();
+        self.file.filetext.get(start..start + size)
     }
 
     pub fn lex(&mut self) -> Fuzzy<Token, Diag> {
@@ -173,14 +178,15 @@ impl<'r> Lexer<'r> {
                     }
                     let err = self
                         .dcx
-                        .struct_err(format!("unknown start of token {c:?}"), self.current_span());
+                        .struct_err(format!("unknown start of token {c:?}"), self.current_span())
+                        .with_code(E0001);
                     return Fuzzy::Err(err);
                 }
                 None => {
-                    let len = self.file.length();
+                    let len = self.file.filetext.len();
                     return Fuzzy::Ok(Token {
                         tt: EOF,
-                        loc: Span::new(len - 1, len),
+                        loc: Span::new(len.saturating_sub(1), len).offset(self.start_pos),
                     });
                 }
             }
@@ -378,8 +384,10 @@ mod tests {
 
     #[test]
     fn lexer_peek_pop() {
-        let dcx = DiagCtxt::new(TEXT1, unit_test_path!());
-        let mut lexer = Lexer::new(unit_test_path!(), TEXT1, &dcx);
+        let sm = SourceMap::new();
+        let file = sm.add_file(unit_test_path!(), TEXT1);
+        let dcx = DiagCtxt::new(&sm);
+        let mut lexer = Lexer::new(&file, &dcx);
 
         assert_eq!(lexer.pop(), Some('R'));
 
@@ -395,14 +403,18 @@ mod tests {
 
         assert_eq!(lexer.pop(), Some(' '));
         assert_eq!(lexer.pop(), Some('🌹'));
+        // the spans are in bytes
+        assert_eq!(lexer.current_span(), Span::new(0, 9));
         assert_eq!(lexer.pop(), None);
     }
 
     #[test]
     fn lexer_identifier_and_keywords() {
-        let text = "abc fun return let mut type true false pub";
-        let dcx = DiagCtxt::new(text, unit_test_path!());
-        let mut lexer = Lexer::new(unit_test_path!(), text, &dcx);
+        let text = "abc fun return let mut type true false pub object enum match var import";
+        let sm = SourceMap::new();
+        let file = sm.add_file(unit_test_path!(), text);
+        let dcx = DiagCtxt::new(&sm);
+        let mut lexer = Lexer::new(&file, &dcx);
         assert_eq!(lexer.lex().unwrap().tt, TokenType::Ident("abc".to_string()));
         assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Fun));
         assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Return));
@@ -412,15 +424,31 @@ mod tests {
         assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::True));
         assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::False));
         assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Pub));
+        assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Object));
+        assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Enum));
+        assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Match));
+        assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Var));
+        assert_eq!(lexer.lex().unwrap().tt, TokenType::KW(Keyword::Import));
         assert_eq!(lexer.lex().unwrap().tt, TokenType::EOF);
     }
 
+    #[test]
+    fn punctuation_display() {
+        use crate::tokens::Punctuation::*;
+
+        let delimiters = [LParen, RParen, LBracket, RBracket, LBrace, RBrace];
+        let text = delimiters.iter().map(|p| p.to_string()).collect::<String>();
+        assert_eq!(text, "()[]{}");
+    }
+
     #[test]
     #[should_panic]
     fn lexer_too_large_int() {
         let text = u128::MAX.to_string();
-        let dcx = DiagCtxt::new(&text, unit_test_path!());
-        let mut lexer = Lexer::new(unit_test_path!(), &text, &dcx);
+        let sm = SourceMap::new();
+        let file = sm.add_file(unit_test_path!(), text);
+        let dcx = DiagCtxt::new(&sm);
+        let mut lexer = Lexer::new(&file, &dcx);
         // Should panic because we unwrap an Fuzzy::Err due to the
         // source code containing a number too large to fit in the int literal
         lexer.lex().unwrap();
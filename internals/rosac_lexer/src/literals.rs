@@ -19,30 +19,38 @@ impl<'r> super::Lexer<'r> {
             Ok(number) => Ok(number),
             Err(ParseUIntError::IntegerOverflow) => Err(self
                 .dcx
-                .struct_err("integer literal is too large", self.current_span())),
+                .struct_err("integer literal is too large", self.current_span())
+                .with_code(E0002)),
             Err(ParseUIntError::DigitOutOfRange(loc)) => {
                 let i = self.idx - num.len().into() + loc.lo;
-                Err(self.dcx.struct_err(
-                    format!(
-                        "digit out of radix {:?}",
-                        &num[loc.clone().range_usize()].chars().next().unwrap()
-                    ),
-                    Span::new(i, i + 1.into()),
-                ))
+                Err(self
+                    .dcx
+                    .struct_err(
+                        format!(
+                            "digit out of radix {:?}",
+                            &num[loc.clone().range_usize()].chars().next().unwrap()
+                        ),
+                        Span::new(i, i + 1.into()),
+                    )
+                    .with_code(E0003))
             }
             Err(ParseUIntError::InvalidCharacter(loc)) => {
                 let i = self.idx - num.len().into() + loc.lo;
-                Err(self.dcx.struct_err(
-                    format!(
-                        "invalid character in literal, {:?}",
-                        &num[loc.clone().range_usize()].chars().next().unwrap()
-                    ),
-                    Span::new(i, i + 1.into()),
-                ))
-            }
-            Err(ParseUIntError::InvalidRadix) => {
-                Err(self.dcx.struct_err("invalid radix", self.current_span()))
+                Err(self
+                    .dcx
+                    .struct_err(
+                        format!(
+                            "invalid character in literal, {:?}",
+                            &num[loc.clone().range_usize()].chars().next().unwrap()
+                        ),
+                        Span::new(i, i + 1.into()),
+                    )
+                    .with_code(E0003))
             }
+            Err(ParseUIntError::InvalidRadix) => Err(self
+                .dcx
+                .struct_err("invalid radix", self.current_span())
+                .with_code(E0003)),
         }
     }
 
@@ -82,7 +90,8 @@ impl<'r> super::Lexer<'r> {
                 _ => {
                     return Fuzzy::Err(
                         self.dcx
-                            .struct_err("unterminated string literal", self.current_span_end()),
+                            .struct_err("unterminated string literal", self.current_span_end())
+                            .with_code(E0004),
                     )
                 }
             }
@@ -108,16 +117,22 @@ impl<'r> super::Lexer<'r> {
             'x' => self.make_hex_es()?,
             'u' => {
                 // TODO: implement the lexing of unicode es
-                return Err(self.dcx.struct_err(
-                    "unicode escape sequence are not yet supported",
-                    Span::new(self.idx - 2.into(), self.idx),
-                ));
+                return Err(self
+                    .dcx
+                    .struct_err(
+                        "unicode escape sequence are not yet supported",
+                        Span::new(self.idx - 2.into(), self.idx),
+                    )
+                    .with_code(E0005));
             }
             _ => {
-                return Err(self.dcx.struct_err(
-                    format!("unknown escape sequence: '\\{es}'"),
-                    Span::new(self.idx - 2.into(), self.idx),
-                ))
+                return Err(self
+                    .dcx
+                    .struct_err(
+                        format!("unknown escape sequence: '\\{es}'"),
+                        Span::new(self.idx - 2.into(), self.idx),
+                    )
+                    .with_code(E0005))
             }
         })
     }
@@ -128,6 +143,7 @@ impl<'r> super::Lexer<'r> {
             str.push(self.pop().ok_or_else(|| {
                 self.dcx
                     .struct_err("unterminated string literal", self.current_span())
+                    .with_code(E0004)
             })?);
         }
 
@@ -165,7 +181,8 @@ impl<'r> super::Lexer<'r> {
                     None => {
                         diags.push(
                             self.dcx
-                                .struct_err("unterminated char literal", self.current_span()),
+                                .struct_err("unterminated char literal", self.current_span())
+                                .with_code(E0006),
                         );
                         Default::default()
                     }
@@ -174,13 +191,16 @@ impl<'r> super::Lexer<'r> {
             Some('\'') => {
                 self.expect('\'');
                 diags.push(if let Some('\'') = self.peek() {
-                    self.dcx.struct_err(
-                        "char literal must be escaped `'`",
-                        Span::new(self.idx - 1.into(), self.idx),
-                    )
+                    self.dcx
+                        .struct_err(
+                            "char literal must be escaped `'`",
+                            Span::new(self.idx - 1.into(), self.idx),
+                        )
+                        .with_code(E0007)
                 } else {
                     self.dcx
                         .struct_err("empty char literal", self.current_span())
+                        .with_code(E0007)
                 });
                 char = Default::default();
             }
@@ -189,10 +209,14 @@ impl<'r> super::Lexer<'r> {
                 char = c;
             }
             None => {
-                return Fuzzy::Err(self.dcx.struct_err(
-                    "unexpected end of file",
-                    Span::new(self.idx - 1.into(), self.idx),
-                ))
+                return Fuzzy::Err(
+                    self.dcx
+                        .struct_err(
+                            "unexpected end of file",
+                            Span::new(self.idx - 1.into(), self.idx),
+                        )
+                        .with_code(E0006),
+                )
             }
         }
         match self.peek() {
@@ -201,7 +225,8 @@ impl<'r> super::Lexer<'r> {
             }
             _ => diags.push(
                 self.dcx
-                    .struct_err("unterminated char literal", self.current_span()),
+                    .struct_err("unterminated char literal", self.current_span())
+                    .with_code(E0006),
             ),
         }
         let tok = Token {
@@ -106,14 +106,14 @@ impl Display for Punctuation {
             f,
             "{}",
             match self {
-                Self::RParen => "(",
-                Self::LParen => ")",
+                Self::RParen => ")",
+                Self::LParen => "(",
 
-                Self::RBracket => "[",
-                Self::LBracket => "]",
+                Self::RBracket => "]",
+                Self::LBracket => "[",
 
-                Self::RBrace => "{",
-                Self::LBrace => "}",
+                Self::RBrace => "}",
+                Self::LBrace => "{",
 
                 Self::Colon => ":",
                 Self::Semi => ";",
@@ -155,6 +155,11 @@ pub enum Keyword {
     If,
     Else,
     Pub,
+    Object,
+    Enum,
+    Match,
+    Var,
+    Import,
 }
 
 impl FromStr for Keyword {
@@ -172,28 +177,59 @@ impl FromStr for Keyword {
             "if" => Keyword::If,
             "else" => Keyword::Else,
             "pub" => Keyword::Pub,
+            "object" => Keyword::Object,
+            "enum" => Keyword::Enum,
+            "match" => Keyword::Match,
+            "var" => Keyword::Var,
+            "import" => Keyword::Import,
             _ => return Err(()),
         })
     }
 }
 
+impl Keyword {
+    /// All the keywords of the language.
+    pub const ALL: [Keyword; 15] = [
+        Keyword::Fun,
+        Keyword::Return,
+        Keyword::Let,
+        Keyword::Mut,
+        Keyword::Type,
+        Keyword::True,
+        Keyword::False,
+        Keyword::If,
+        Keyword::Else,
+        Keyword::Pub,
+        Keyword::Object,
+        Keyword::Enum,
+        Keyword::Match,
+        Keyword::Var,
+        Keyword::Import,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fun => "fun",
+            Self::Return => "return",
+            Self::Let => "let",
+            Self::Mut => "mut",
+            Self::Type => "type",
+            Self::True => "true",
+            Self::False => "false",
+            Self::If => "if",
+            Self::Else => "else",
+            Self::Pub => "pub",
+            Self::Object => "object",
+            Self::Enum => "enum",
+            Self::Match => "match",
+            Self::Var => "var",
+            Self::Import => "import",
+        }
+    }
+}
+
 impl Display for Keyword {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Fun => "fun",
-                Self::Return => "return",
-                Self::Let => "let",
-                Self::Mut => "mut",
-                Self::Type => "type",
-                Self::True => "true",
-                Self::False => "false",
-                Self::If => "if",
-                Self::Else => "else",
-                Self::Pub => "pub",
-            }
-        )
+        write!(f, "{}", self.as_str())
     }
 }
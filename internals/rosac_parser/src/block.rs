@@ -34,7 +34,8 @@ impl<N: AstNode<Output = N> + Location> AstNode for Block<N> {
             return Fuzzy::Err(
                 parser
                     .dcx()
-                    .struct_err(expected_tok_msg("block", [EOF]), loc),
+                    .struct_err(expected_tok_msg("block", [EOF]), loc)
+                    .with_code(E0008),
             );
         };
 
@@ -45,7 +46,12 @@ impl<N: AstNode<Output = N> + Location> AstNode for Block<N> {
                     .map(|t| t.loc.clone())
                     .unwrap_or_default();
 
-                return Fuzzy::Err(parser.dcx().struct_err("a block may not be empty", loc));
+                return Fuzzy::Err(
+                    parser
+                        .dcx()
+                        .struct_err("a block may not be empty", loc)
+                        .with_code(E0009),
+                );
             }
         }
 
@@ -53,9 +59,29 @@ impl<N: AstNode<Output = N> + Location> AstNode for Block<N> {
             parser.consume_tok();
         }
         parser.indent(gap);
+        let recovery = parser.recovery_point();
+        let mut diags = Vec::new();
 
         loop {
-            content.push(parse!(parser => N));
+            let lo = parser.peek_tok().loc.lo;
+            match N::parse(parser) {
+                Fuzzy::Ok(elem) => content.push(elem),
+                Fuzzy::Fuzzy(elem, elem_diags) => {
+                    content.push(elem);
+                    parser.dcx().emit_diags(elem_diags);
+                }
+                Fuzzy::Err(diag) => {
+                    // the element is skipped up to the next line of the
+                    // block, the following elements are still parsed.
+                    diags.push(diag);
+                    parser.restore(recovery);
+                    let resume = parser.synchronize(gap);
+                    content.extend(N::error_node(parser.span_since(lo)));
+                    if !resume {
+                        break;
+                    }
+                }
+            }
 
             // we compute the indent level here and how many new lines we need
             // to consume
@@ -68,12 +94,14 @@ impl<N: AstNode<Output = N> + Location> AstNode for Block<N> {
                 return Fuzzy::Err(
                     parser
                         .dcx()
-                        .struct_err(expected_tok_msg("block", [EOF]), loc),
+                        .struct_err(expected_tok_msg("block", [EOF]), loc)
+                        .with_code(E0008),
                 );
             };
 
-            // if the indent level don't match we break.
-            if gap != parser.last_indent().unwrap() {
+            // if the indent level don't match we break, a declaration also
+            // ends the block.
+            if gap != parser.last_indent().unwrap() || parser.starts_decl(til_next) {
                 break;
             }
 
@@ -84,12 +112,20 @@ impl<N: AstNode<Output = N> + Location> AstNode for Block<N> {
         }
 
         parser.dedent();
-        // Here, we unwrap because we know for sure we have at one thing
-        let loc = Span::from_ends(
-            content.first().unwrap().loc(),
-            content.last().unwrap().loc(),
-        );
 
-        Fuzzy::Ok(Block { content, loc })
+        // every element failed to parse, the last error is the one of the
+        // block.
+        let (Some(first), Some(last)) = (content.first(), content.last()) else {
+            let err = diags.pop().unwrap();
+            parser.dcx().emit_diags(diags);
+            return Fuzzy::Err(err);
+        };
+        let loc = Span::from_ends(first.loc(), last.loc());
+
+        if diags.is_empty() {
+            Fuzzy::Ok(Block { content, loc })
+        } else {
+            Fuzzy::Fuzzy(Block { content, loc }, diags)
+        }
     }
 }
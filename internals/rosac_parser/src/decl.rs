@@ -1,5 +1,7 @@
 //! Module responsible for parsing declarations like function, types, imports..
 
+use rosa_comm::edit_distance::find_best_match;
+
 use crate::prelude::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,16 +12,35 @@ pub enum Visibility {
 
 #[derive(Debug, Clone)]
 pub struct Declaration {
-    // TODO: remake the visibility system, put the 'vis' inside of each
-    // 'DeclarationInner' but still parse it in the impl of Declaration and
-    // then pass it as a parameter to the parsing functions.
-    pub vis: Visibility,
     pub decl: DeclarationInner,
     pub loc: Span,
 }
 
 derive_loc!(Declaration);
 
+impl Declaration {
+    /// Returns the visibility of the declaration, imports are always private.
+    pub fn vis(&self) -> Visibility {
+        match &self.decl {
+            DeclarationInner::Function { vis, .. }
+            | DeclarationInner::Object { vis, .. }
+            | DeclarationInner::Enum { vis, .. } => vis.clone(),
+            DeclarationInner::Import { .. } | DeclarationInner::Error { .. } => Visibility::Private,
+        }
+    }
+
+    /// Returns the span of the name of the declaration, the span of the whole
+    /// declaration for imports and errors.
+    pub fn name_loc(&self) -> Span {
+        match &self.decl {
+            DeclarationInner::Function { name_loc, .. }
+            | DeclarationInner::Object { name_loc, .. }
+            | DeclarationInner::Enum { name_loc, .. } => name_loc.clone(),
+            DeclarationInner::Import { .. } | DeclarationInner::Error { .. } => self.loc.clone(),
+        }
+    }
+}
+
 impl AstNode for Declaration {
     type Output = Self;
 
@@ -33,14 +54,46 @@ impl AstNode for Declaration {
             Token {
                 tt: KW(Keyword::Fun),
                 ..
-            } => parse!(@fn parser => parse_fun_decl),
+            } => parse!(@fn parser => parse_fun_decl, vis),
+            Token {
+                tt: KW(Keyword::Type),
+                ..
+            } => parse!(@fn parser => parse_type_decl, vis),
+            Token {
+                tt: KW(Keyword::Import),
+                ..
+            } => {
+                if vis == Visibility::Public {
+                    let diag = parser
+                        .dcx()
+                        .struct_err("an import cannot be public", vis_loc.clone())
+                        .with_code(E0010);
+                    parser.dcx().emit_diag(diag);
+                }
+                parse!(@fn parser => parse_import_decl)
+            }
             t => {
                 let t = t.clone();
-                return Fuzzy::Err(
-                    parser
-                        .dcx()
-                        .struct_err(expected_tok_msg(t.tt, [AstPart::Declaration]), t.loc),
-                );
+                let mut diag = parser
+                    .dcx()
+                    .struct_err(
+                        expected_tok_msg(&t.tt, [AstPart::Declaration]),
+                        t.loc.clone(),
+                    )
+                    .with_code(E0008);
+                // a misspelled keyword starting a declaration.
+                if let Ident(name) = &t.tt {
+                    let keywords = [Keyword::Fun, Keyword::Type, Keyword::Import, Keyword::Pub];
+                    if let Some(kw) = find_best_match(name, keywords.iter().map(Keyword::as_str)) {
+                        diag = diag.with_suggestion(
+                            t.loc,
+                            "a keyword with a similar name exists",
+                            kw,
+                            Applicability::MaybeIncorrect,
+                        );
+                    }
+                }
+                return Fuzzy::Err(diag);
             }
         };
 
@@ -49,29 +102,204 @@ impl AstNode for Declaration {
         } else {
             Span::new(vis_loc.lo, decl_loc.hi)
         };
-        Fuzzy::Ok(Declaration { vis, decl, loc })
+        Fuzzy::Ok(Declaration { decl, loc })
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum DeclarationInner {
     Function {
+        vis: Visibility,
         name: String,
-        args: Vec<(String, Type)>,
+        name_loc: Span,
+        args: Vec<Arg>,
         ret: Option<Type>,
         block: Block<Statement>,
     },
+    Object {
+        vis: Visibility,
+        name: String,
+        name_loc: Span,
+        fields: Vec<Field>,
+    },
+    Enum {
+        vis: Visibility,
+        name: String,
+        name_loc: Span,
+        variants: Vec<Variant>,
+    },
+    /// Import of a module, e.g: `import std/io` has the path `["std", "io"]`
+    Import { path: Vec<String> },
+    /// A declaration that failed to parse, inserted by the error recovery.
+    /// The name of a function is kept, so its uses are not reported as
+    /// unresolved.
+    Error { name: Option<String> },
+}
+
+/// An argument of a function declaration, e.g: `var out: int`, the `var`
+/// arguments are passed by reference.
+#[derive(Debug, Clone)]
+pub struct Arg {
+    pub name: String,
+    pub name_loc: Span,
+    pub ty: Type,
+    pub var: bool,
+    pub loc: Span,
+}
+
+derive_loc!(Arg);
+
+impl AstNode for Arg {
+    type Output = Self;
+
+    fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
+        let (var, var_loc) = expect_token!(
+            parser => [KW(Keyword::Var), true]
+            else { (false, Span::ZERO) }
+        );
+
+        let (name, name_loc) =
+            expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+        expect_token!(parser => [Punct(Punctuation::Colon), ()], [FmtToken::Punct(Punctuation::Colon)]);
+
+        let ty = parse!(parser => Type);
+
+        let lo = if var { var_loc.lo } else { name_loc.lo };
+        Fuzzy::Ok(Arg {
+            name,
+            name_loc,
+            loc: Span::new(lo, ty.loc.hi),
+            ty,
+            var,
+        })
+    }
+}
+
+/// A field of an object type declaration, e.g: `age: uint8`
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+    pub loc: Span,
+}
+
+derive_loc!(Field);
+
+impl AstNode for Field {
+    type Output = Self;
+
+    fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
+        let (name, Span { lo, .. }) =
+            expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+        expect_token!(parser => [Punct(Punctuation::Colon), ()], [FmtToken::Punct(Punctuation::Colon)]);
+
+        let ty = parse!(parser => Type);
+        let mut hi = ty.loc.hi;
+
+        // the fields may be separated by commas
+        if let Some(Token {
+            tt: Punct(Punctuation::Comma),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            (_, Span { hi, .. }) = expect_token!(
+                parser => [Punct(Punctuation::Comma), ()],
+                [FmtToken::Punct(Punctuation::Comma)]
+            );
+        }
+
+        Fuzzy::Ok(Field {
+            name,
+            ty,
+            loc: Span::new(lo, hi),
+        })
+    }
+}
+
+/// A variant of an enum type declaration with the types of its payload, e.g:
+/// `Rect(int, int)` or `Empty`
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub name: String,
+    pub payload: Vec<Type>,
+    pub loc: Span,
+}
+
+derive_loc!(Variant);
+
+impl AstNode for Variant {
+    type Output = Self;
+
+    fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
+        let (name, Span { lo, mut hi }) =
+            expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+        let mut payload = Vec::new();
+        if let Some(Token {
+            tt: Punct(Punctuation::LParen),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            expect_token!(parser => [Punct(Punctuation::LParen), ()], [FmtToken::Punct(Punctuation::LParen)]);
+
+            loop {
+                if let Some(Token {
+                    tt: Punct(Punctuation::RParen),
+                    ..
+                }) = parser.try_peek_tok()
+                {
+                    break;
+                }
+
+                payload.push(parse!(parser => Type));
+
+                expect_token!(
+                    parser => [
+                        Punct(Punctuation::Comma), (); Punct(Punctuation::RParen), (), in break
+                    ],
+                    [FmtToken::Punct(Punctuation::Comma), FmtToken::Punct(Punctuation::RParen)]
+                );
+            }
+
+            (_, Span { hi, .. }) = expect_token!(
+                parser => [Punct(Punctuation::RParen), ()],
+                [FmtToken::Punct(Punctuation::RParen)]
+            );
+        }
+
+        // the variants may be separated by commas
+        if let Some(Token {
+            tt: Punct(Punctuation::Comma),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            (_, Span { hi, .. }) = expect_token!(
+                parser => [Punct(Punctuation::Comma), ()],
+                [FmtToken::Punct(Punctuation::Comma)]
+            );
+        }
+
+        Fuzzy::Ok(Variant {
+            name,
+            payload,
+            loc: Span::new(lo, hi),
+        })
+    }
 }
 
 pub fn parse_fun_decl(
     parser: &mut Parser<'_, impl AbsLexer>,
+    vis: Visibility,
 ) -> Fuzzy<(DeclarationInner, Span), Diag> {
     let mut loc = Span::default();
     let (_, Span { lo, .. }) =
         expect_token!(parser => [KW(Keyword::Fun), ()], [FmtToken::KW(Keyword::Fun)]);
     loc.lo = lo;
 
-    let (name, _) = expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+    let (name, name_loc) =
+        expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
 
     expect_token!(parser => [Punct(Punctuation::LParen), ()], [FmtToken::Punct(Punctuation::LParen)]);
 
@@ -85,14 +313,7 @@ pub fn parse_fun_decl(
             break;
         }
 
-        let (name, _) =
-            expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
-
-        expect_token!(parser => [Punct(Punctuation::Colon), ()], [FmtToken::Punct(Punctuation::Colon)]);
-
-        let ty = parse!(parser => Type);
-
-        args.push((name, ty));
+        args.push(parse!(parser => Arg));
         expect_token!(
             parser => [
                 Punct(Punctuation::Comma), (); Punct(Punctuation::RParen), (), in break
@@ -123,7 +344,9 @@ pub fn parse_fun_decl(
     }
     Fuzzy::Ok((
         DeclarationInner::Function {
+            vis,
             name,
+            name_loc,
             args,
             ret,
             block,
@@ -131,3 +354,74 @@ pub fn parse_fun_decl(
         loc,
     ))
 }
+
+pub fn parse_type_decl(
+    parser: &mut Parser<'_, impl AbsLexer>,
+    vis: Visibility,
+) -> Fuzzy<(DeclarationInner, Span), Diag> {
+    let (_, Span { lo, .. }) =
+        expect_token!(parser => [KW(Keyword::Type), ()], [FmtToken::KW(Keyword::Type)]);
+
+    let (name, name_loc) =
+        expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+    expect_token!(parser => [Punct(Punctuation::Equal), ()], [FmtToken::Punct(Punctuation::Equal)]);
+
+    let (is_enum, _) = expect_token!(
+        parser => [KW(Keyword::Object), false; KW(Keyword::Enum), true],
+        [FmtToken::KW(Keyword::Object), FmtToken::KW(Keyword::Enum)]
+    );
+
+    if is_enum {
+        let variants = parse!(parser => Block<Variant>);
+
+        return Fuzzy::Ok((
+            DeclarationInner::Enum {
+                vis,
+                name,
+                name_loc,
+                variants: variants.content,
+            },
+            Span::new(lo, variants.loc.hi),
+        ));
+    }
+
+    let fields = parse!(parser => Block<Field>);
+
+    Fuzzy::Ok((
+        DeclarationInner::Object {
+            vis,
+            name,
+            name_loc,
+            fields: fields.content,
+        },
+        Span::new(lo, fields.loc.hi),
+    ))
+}
+
+pub fn parse_import_decl(
+    parser: &mut Parser<'_, impl AbsLexer>,
+) -> Fuzzy<(DeclarationInner, Span), Diag> {
+    let (_, Span { lo, .. }) =
+        expect_token!(parser => [KW(Keyword::Import), ()], [FmtToken::KW(Keyword::Import)]);
+
+    let mut path = Vec::new();
+    let hi = loop {
+        let (name, Span { hi, .. }) =
+            expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+        path.push(name);
+
+        // the names of the path are separated by slashes
+        if let Some(Token {
+            tt: Punct(Punctuation::Slash),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            expect_token!(parser => [Punct(Punctuation::Slash), ()], [FmtToken::Punct(Punctuation::Slash)]);
+        } else {
+            break hi;
+        }
+    };
+
+    Fuzzy::Ok((DeclarationInner::Import { path }, Span::new(lo, hi)))
+}
@@ -1,5 +1,7 @@
 //! Module responsible for parsing expressions.
 
+use std::{cell::RefCell, fmt::Display};
+
 use crate::prelude::*;
 
 /// An operator, either a binary operator or a unary operator.
@@ -73,6 +75,55 @@ impl BinaryOp {
             _ => return None,
         })
     }
+
+    /// Is the binary operator an arithmetic operator, taking integers and
+    /// producing an integer
+    pub fn is_arithmetic(&self) -> bool {
+        !self.is_comparison()
+    }
+
+    /// Is the binary operator a comparison of the order of its operands, like
+    /// `<` or `>=`
+    pub fn is_ordering(&self) -> bool {
+        use BinaryOp as BOp;
+        matches!(
+            self,
+            BOp::CompLT | BOp::CompGT | BOp::CompLTE | BOp::CompGTE
+        )
+    }
+
+    /// Is the binary operator a comparison, producing a boolean
+    pub fn is_comparison(&self) -> bool {
+        use BinaryOp as BOp;
+        matches!(
+            self,
+            BOp::CompLT | BOp::CompGT | BOp::CompLTE | BOp::CompGTE | BOp::CompEq | BOp::CompNe
+        )
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Mul => "*",
+                Self::Div => "/",
+                Self::Rem => "%",
+                Self::Add => "+",
+                Self::Sub => "-",
+                Self::RShift => ">>",
+                Self::LShift => "<<",
+                Self::CompLT => "<",
+                Self::CompGT => ">",
+                Self::CompLTE => "<=",
+                Self::CompGTE => ">=",
+                Self::CompEq => "==",
+                Self::CompNe => "!=",
+            }
+        )
+    }
 }
 
 /// Unary Operators
@@ -111,6 +162,15 @@ impl UnaryOp {
     }
 }
 
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Negation => write!(f, "-"),
+            Self::Not => write!(f, "!"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Associativity {
     LeftToRight,
@@ -162,49 +222,128 @@ pub enum ExpressionInner {
         op: UnaryOp,
         operand: Box<Expression>,
     },
+    CallExpr {
+        callee: Box<Expression>,
+        args: Vec<Expression>,
+    },
+    FieldExpr {
+        expr: Box<Expression>,
+        field: String,
+    },
+    ObjectExpr {
+        name: String,
+        fields: Vec<(String, Expression)>,
+    },
+    IfExpr {
+        predicate: Box<Expression>,
+        body: Block<Statement>,
+        else_branch: Option<Block<Statement>>,
+    },
+    MatchExpr {
+        scrutinee: Box<Expression>,
+        arms: Vec<MatchArm>,
+    },
+    IndexExpr {
+        expr: Box<Expression>,
+        index: Box<Expression>,
+    },
 
     // primary expression
-    IntLiteral(u64),
+    IntLiteral {
+        value: u64,
+        /// The type of the literal, it is `None` until it's set by the type
+        /// checker of the semantic analyzer.
+        ty: RefCell<Option<TypeInner>>,
+    },
     BoolLiteral(bool),
     CharLiteral(char),
     StrLiteral(String),
+    ArrayLiteral(Vec<Expression>),
     SymbolExpr(Symbol),
+    /// An expression that failed to parse, inserted by the error recovery.
+    Error,
 }
 
 impl AstNode for ExpressionInner {
     type Output = Expression;
 
     fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
-        match parser.peek_tok() {
-            Token { tt: Int(_), .. } => parse_intlit_expr(parser),
-            Token {
-                tt: KW(Keyword::True | Keyword::False),
-                ..
-            } => parse_boollit_expr(parser),
-            Token { tt: Char(_), .. } => parse_charlit_expr(parser),
-            Token { tt: Str(_), .. } => parse_strlit_expr(parser),
-            Token { tt: Ident(_), .. } => parse_symbol_expr(parser),
-            Token {
-                tt: Punct(punct), ..
-            } if UnaryOp::from_punct(punct.clone()).is_some_and(|op| op.is_left()) => {
-                parse_left_unary_expr(parser)
-            }
-            t => {
-                let t = t.clone();
-                Fuzzy::Err(
-                    parser
-                        .dcx()
-                        .struct_err(expected_tok_msg(t.tt, [AstPart::Expression]), t.loc),
-                )
+        let mut expr = parse!(@fn parser => parse_primary_expr);
+
+        loop {
+            expr = match parser.try_peek_tok() {
+                Some(Token {
+                    tt: Punct(Punctuation::LParen),
+                    ..
+                }) => parse!(@fn parser => parse_call_expr, expr),
+                Some(Token {
+                    tt: Punct(Punctuation::Dot),
+                    ..
+                }) => parse!(@fn parser => parse_field_expr, expr),
+                Some(Token {
+                    tt: Punct(Punctuation::LBracket),
+                    ..
+                }) => parse!(@fn parser => parse_index_expr, expr),
+                _ => break,
+            };
+        }
+
+        Fuzzy::Ok(expr)
+    }
+}
+
+pub fn parse_primary_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
+    match parser.peek_tok() {
+        Token { tt: Int(_), .. } => parse_intlit_expr(parser),
+        Token {
+            tt: KW(Keyword::True | Keyword::False),
+            ..
+        } => parse_boollit_expr(parser),
+        Token { tt: Char(_), .. } => parse_charlit_expr(parser),
+        Token { tt: Str(_), .. } => parse_strlit_expr(parser),
+        Token {
+            tt: Punct(Punctuation::LBracket),
+            ..
+        } => parse_array_expr(parser),
+        Token { tt: Ident(_), .. } => {
+            if is_object_expr(parser) {
+                parse_object_expr(parser)
+            } else {
+                parse_symbol_expr(parser)
             }
         }
+        Token {
+            tt: KW(Keyword::If),
+            ..
+        } => parse_if_expr(parser),
+        Token {
+            tt: KW(Keyword::Match),
+            ..
+        } => parse_match_expr(parser),
+        Token {
+            tt: Punct(punct), ..
+        } if UnaryOp::from_punct(punct.clone()).is_some_and(|op| op.is_left()) => {
+            parse_left_unary_expr(parser)
+        }
+        t => {
+            let t = t.clone();
+            Fuzzy::Err(
+                parser
+                    .dcx()
+                    .struct_err(expected_tok_msg(t.tt, [AstPart::Expression]), t.loc)
+                    .with_code(E0008),
+            )
+        }
     }
 }
 
 pub fn parse_intlit_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
     let (i, loc) = expect_token!(parser => [Int(i), *i], [FmtToken::IntLiteral]);
     Fuzzy::Ok(Expression {
-        expr: ExpressionInner::IntLiteral(i),
+        expr: ExpressionInner::IntLiteral {
+            value: i,
+            ty: RefCell::new(None),
+        },
         loc,
     })
 }
@@ -281,13 +420,16 @@ pub fn parse_left_unary_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Ex
             return Fuzzy::Err(
                 parser
                     .dcx()
-                    .struct_err(expected_tok_msg(punct, ["left unary operator"]), lhs),
+                    .struct_err(expected_tok_msg(punct, ["left unary operator"]), lhs)
+                    .with_code(E0008),
             )
         }
     };
 
+    let previous_precedence = parser.current_precedence;
     parser.current_precedence = operator_precedence(op.clone()).1;
     let operand = Box::new(parse!(parser => Expression));
+    parser.current_precedence = previous_precedence;
 
     Fuzzy::Ok(Expression {
         loc: Span::from_ends(lhs, operand.loc.clone()),
@@ -328,6 +470,76 @@ pub fn parse_strlit_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expres
     })
 }
 
+pub fn parse_array_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
+    let (_, Span { lo, .. }) = expect_token!(
+        parser => [Punct(Punctuation::LBracket), ()],
+        [FmtToken::Punct(Punctuation::LBracket)]
+    );
+
+    // like in a call, the precedence doesn't matter in the elements
+    let previous_precedence = parser.current_precedence;
+    parser.current_precedence = 0;
+
+    let mut elems = Vec::new();
+    loop {
+        if let Some(Token {
+            tt: Punct(Punctuation::RBracket),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            break;
+        }
+
+        elems.push(parse!(parser => Expression));
+
+        expect_token!(
+            parser => [
+                Punct(Punctuation::Comma), (); Punct(Punctuation::RBracket), (), in break
+            ],
+            [FmtToken::Punct(Punctuation::Comma), FmtToken::Punct(Punctuation::RBracket)]
+        );
+    }
+    parser.current_precedence = previous_precedence;
+
+    let (_, Span { hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::RBracket), ()],
+        [FmtToken::Punct(Punctuation::RBracket)]
+    );
+
+    Fuzzy::Ok(Expression {
+        expr: ExpressionInner::ArrayLiteral(elems),
+        loc: Span::new(lo, hi),
+    })
+}
+
+pub fn parse_index_expr(
+    parser: &mut Parser<'_, impl AbsLexer>,
+    expr: Expression,
+) -> Fuzzy<Expression, Diag> {
+    expect_token!(
+        parser => [Punct(Punctuation::LBracket), ()],
+        [FmtToken::Punct(Punctuation::LBracket)]
+    );
+
+    let previous_precedence = parser.current_precedence;
+    parser.current_precedence = 0;
+    let index = parse!(parser => Expression);
+    parser.current_precedence = previous_precedence;
+
+    let (_, Span { hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::RBracket), ()],
+        [FmtToken::Punct(Punctuation::RBracket)]
+    );
+
+    Fuzzy::Ok(Expression {
+        loc: Span::new(expr.loc.lo, hi),
+        expr: ExpressionInner::IndexExpr {
+            expr: Box::new(expr),
+            index: Box::new(index),
+        },
+    })
+}
+
 pub fn parse_symbol_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
     let (id, loc) = expect_token!(parser => [Ident(id), id.clone()], [FmtToken::Identifier]);
     Fuzzy::Ok(Expression {
@@ -335,3 +547,268 @@ pub fn parse_symbol_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expres
         loc,
     })
 }
+
+pub fn parse_if_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
+    let (_, Span { lo, .. }) =
+        expect_token!(parser => [KW(Keyword::If), ()], [FmtToken::KW(Keyword::If)]);
+    let predicate = Box::new(parse!(parser => Expression));
+
+    if let Err(diag) = expect_block_colon(parser, predicate.loc.hi, "after the predicate") {
+        return Fuzzy::Err(diag);
+    }
+    let body = parse!(parser => Block<Statement>);
+    let mut hi = body.loc.hi;
+
+    // the `else` may be on its own line, at the same indentation level as the
+    // `if`, so we skip the new lines before it.
+    if let Some(Token { tt: NewLine, .. }) = parser.try_peek_tok() {
+        if let Some((gap, til_next)) = parser.compute_indent() {
+            if Some(gap) == parser.last_indent()
+                && matches!(
+                    parser.nth_tok(til_next),
+                    Some(Token {
+                        tt: KW(Keyword::Else),
+                        ..
+                    })
+                )
+            {
+                for _ in 0..til_next {
+                    parser.consume_tok();
+                }
+            }
+        }
+    }
+
+    let else_branch = if let Some(Token {
+        tt: KW(Keyword::Else),
+        ..
+    }) = parser.try_peek_tok()
+    {
+        let (_, else_loc) =
+            expect_token!(parser => [KW(Keyword::Else), ()], [FmtToken::KW(Keyword::Else)]);
+
+        if let Err(diag) = expect_block_colon(parser, else_loc.hi, "after `else`") {
+            return Fuzzy::Err(diag);
+        }
+
+        let r#else = parse!(parser => Block<Statement>);
+        hi = r#else.loc.hi;
+        Some(r#else)
+    } else {
+        None
+    };
+
+    Fuzzy::Ok(Expression {
+        expr: ExpressionInner::IfExpr {
+            predicate,
+            body,
+            else_branch,
+        },
+        loc: Span::new(lo, hi),
+    })
+}
+
+/// Expects the `:` introducing the block of an `if` or an `else`, when the
+/// line ends without it the `:` is suggested at the position `after`.
+fn expect_block_colon(
+    parser: &mut Parser<'_, impl AbsLexer>,
+    after: BytePos,
+    place: &str,
+) -> Result<(), Diag> {
+    expect_token!(parser => [Punct(Punctuation::Colon), ()] else {
+        let found = parser.peek_tok().clone();
+        let diag = parser
+            .dcx()
+            .struct_err(
+                expected_tok_msg(&found.tt, [FmtToken::Punct(Punctuation::Colon)]),
+                found.loc,
+            )
+            .with_code(E0008);
+
+        return Err(if found.tt == NewLine {
+            diag.with_suggestion(
+                Span::from_inner(after, after),
+                format!("add `:` {place}"),
+                ":",
+                Applicability::MachineApplicable,
+            )
+        } else {
+            diag
+        });
+    });
+    Ok(())
+}
+
+/// An arm of a match expression, e.g: `Circle(r): r * r * 3`
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Block<Statement>,
+    pub loc: Span,
+}
+
+derive_loc!(MatchArm);
+
+impl AstNode for MatchArm {
+    type Output = Self;
+
+    fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
+        let pattern = parse!(parser => Pattern);
+
+        expect_token!(
+            parser => [Punct(Punctuation::Colon), ()],
+            [FmtToken::Punct(Punctuation::Colon)]
+        );
+        let body = parse!(parser => Block<Statement>);
+
+        Fuzzy::Ok(MatchArm {
+            loc: Span::new(pattern.loc.lo, body.loc.hi),
+            pattern,
+            body,
+        })
+    }
+}
+
+pub fn parse_match_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
+    let (_, Span { lo, .. }) =
+        expect_token!(parser => [KW(Keyword::Match), ()], [FmtToken::KW(Keyword::Match)]);
+    let scrutinee = Box::new(parse!(parser => Expression));
+
+    expect_token!(
+        parser => [Punct(Punctuation::Colon), ()],
+        [FmtToken::Punct(Punctuation::Colon)]
+    );
+    let arms = parse!(parser => Block<MatchArm>);
+
+    Fuzzy::Ok(Expression {
+        expr: ExpressionInner::MatchExpr {
+            scrutinee,
+            arms: arms.content,
+        },
+        loc: Span::new(lo, arms.loc.hi),
+    })
+}
+
+pub fn parse_call_expr(
+    parser: &mut Parser<'_, impl AbsLexer>,
+    callee: Expression,
+) -> Fuzzy<Expression, Diag> {
+    expect_token!(parser => [Punct(Punctuation::LParen), ()], [FmtToken::Punct(Punctuation::LParen)]);
+
+    // the arguments are parsed like any other expressions, no matter the
+    // precedence of the operator the call is in.
+    let previous_precedence = parser.current_precedence;
+    parser.current_precedence = 0;
+
+    let mut args = Vec::new();
+    loop {
+        if let Some(Token {
+            tt: Punct(Punctuation::RParen),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            break;
+        }
+
+        args.push(parse!(parser => Expression));
+
+        expect_token!(
+            parser => [
+                Punct(Punctuation::Comma), (); Punct(Punctuation::RParen), (), in break
+            ],
+            [FmtToken::Punct(Punctuation::Comma), FmtToken::Punct(Punctuation::RParen)]
+        );
+    }
+    parser.current_precedence = previous_precedence;
+
+    let (_, Span { hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::RParen), ()],
+        [FmtToken::Punct(Punctuation::RParen)]
+    );
+
+    Fuzzy::Ok(Expression {
+        loc: Span::new(callee.loc.lo, hi),
+        expr: ExpressionInner::CallExpr {
+            callee: Box::new(callee),
+            args,
+        },
+    })
+}
+
+pub fn parse_field_expr(
+    parser: &mut Parser<'_, impl AbsLexer>,
+    expr: Expression,
+) -> Fuzzy<Expression, Diag> {
+    expect_token!(parser => [Punct(Punctuation::Dot), ()], [FmtToken::Punct(Punctuation::Dot)]);
+
+    let (field, Span { hi, .. }) =
+        expect_token!(parser => [Ident(field), field.clone()], [FmtToken::Identifier]);
+
+    Fuzzy::Ok(Expression {
+        loc: Span::new(expr.loc.lo, hi),
+        expr: ExpressionInner::FieldExpr {
+            expr: Box::new(expr),
+            field,
+        },
+    })
+}
+
+/// Is the next expression the construction of an object, it starts like a
+/// call but the arguments are named, e.g: `Human(name: "Bob", age: 42)`
+fn is_object_expr(parser: &mut Parser<'_, impl AbsLexer>) -> bool {
+    matches!(
+        parser.nth_tok(1).map(|t| &t.tt),
+        Some(Punct(Punctuation::LParen))
+    ) && matches!(parser.nth_tok(2).map(|t| &t.tt), Some(Ident(_)))
+        && matches!(
+            parser.nth_tok(3).map(|t| &t.tt),
+            Some(Punct(Punctuation::Colon))
+        )
+}
+
+pub fn parse_object_expr(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Expression, Diag> {
+    let (name, Span { lo, .. }) =
+        expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+    expect_token!(parser => [Punct(Punctuation::LParen), ()], [FmtToken::Punct(Punctuation::LParen)]);
+
+    // like in a call, the precedence doesn't matter in the fields
+    let previous_precedence = parser.current_precedence;
+    parser.current_precedence = 0;
+
+    let mut fields = Vec::new();
+    loop {
+        if let Some(Token {
+            tt: Punct(Punctuation::RParen),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            break;
+        }
+
+        let (field, _) =
+            expect_token!(parser => [Ident(field), field.clone()], [FmtToken::Identifier]);
+
+        expect_token!(parser => [Punct(Punctuation::Colon), ()], [FmtToken::Punct(Punctuation::Colon)]);
+
+        fields.push((field, parse!(parser => Expression)));
+
+        expect_token!(
+            parser => [
+                Punct(Punctuation::Comma), (); Punct(Punctuation::RParen), (), in break
+            ],
+            [FmtToken::Punct(Punctuation::Comma), FmtToken::Punct(Punctuation::RParen)]
+        );
+    }
+    parser.current_precedence = previous_precedence;
+
+    let (_, Span { hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::RParen), ()],
+        [FmtToken::Punct(Punctuation::RParen)]
+    );
+
+    Fuzzy::Ok(Expression {
+        expr: ExpressionInner::ObjectExpr { name, fields },
+        loc: Span::new(lo, hi),
+    })
+}
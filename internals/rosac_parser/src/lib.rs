@@ -9,6 +9,7 @@ use crate::prelude::*;
 pub mod block;
 pub mod decl;
 pub mod expr;
+pub mod pattern;
 pub mod precedence;
 pub mod prelude;
 pub mod stmt;
@@ -22,6 +23,8 @@ pub struct Parser<'r, L: AbsLexer = BufferedLexer<'r>> {
     current_precedence: PrecedenceValue,
     /// Indent stack.
     indent: Vec<BytePos>,
+    /// End of the last token consumed.
+    last_hi: BytePos,
     /// used to be able to make the L type default to BufferedLexer.
     _marker: PhantomData<&'r ()>,
 }
@@ -32,18 +35,23 @@ impl<'r, L: AbsLexer> Parser<'r, L> {
             lexer,
             current_precedence: 0,
             indent: vec![0.into()],
+            last_hi: BytePos::ZERO,
             _marker: PhantomData,
         }
     }
 
     #[inline]
-    pub fn dcx(&self) -> &DiagCtxt {
+    pub fn dcx(&self) -> &DiagCtxt<'_> {
         self.lexer.dcx()
     }
 
     #[inline]
     pub fn consume_tok(&mut self) -> Option<Token> {
-        self.lexer.consume()
+        let tok = self.lexer.consume();
+        if let Some(tok) = &tok {
+            self.last_hi = tok.loc.hi;
+        }
+        tok
     }
 
     #[inline]
@@ -81,6 +89,8 @@ impl<'r, L: AbsLexer> Parser<'r, L> {
                 break;
             }
 
+            let lo = self.peek_tok().loc.lo;
+            let name = self.fun_decl_name();
             match Declaration::parse(self) {
                 Fuzzy::Ok(decl) => decls.push(decl),
                 Fuzzy::Fuzzy(decl, diags) => {
@@ -88,10 +98,16 @@ impl<'r, L: AbsLexer> Parser<'r, L> {
                     self.dcx().emit_diags(diags);
                 }
                 Fuzzy::Err(diag) => {
-                    // Here we break out of the loop because we didn't have a thing that
-                    // correctly parses...
+                    // the declaration is skipped, and the parsing resumes at
+                    // the next one to report the errors that follow.
                     self.dcx().emit_diag(diag);
-                    break;
+                    self.indent.truncate(1);
+                    self.current_precedence = 0;
+                    self.synchronize(BytePos::ZERO);
+                    decls.push(Declaration {
+                        decl: DeclarationInner::Error { name },
+                        loc: self.span_since(lo),
+                    });
                 }
             }
         }
@@ -99,6 +115,83 @@ impl<'r, L: AbsLexer> Parser<'r, L> {
         decls
     }
 
+    /// Skips the tokens of a construct that failed to parse, until a
+    /// synchronization point: the end of file or a new line followed by a line
+    /// indented at most at `indent` or starting a declaration. The new lines
+    /// are not consumed. Returns false at the end of file or before a
+    /// declaration, the enclosing block cannot continue.
+    pub fn synchronize(&mut self, indent: BytePos) -> bool {
+        loop {
+            match self.try_peek_tok().map(|t| &t.tt) {
+                None | Some(TokenType::EOF) => return false,
+                Some(TokenType::NewLine) => {
+                    let Some((gap, til_next)) = self.compute_indent() else {
+                        return false;
+                    };
+                    if self.starts_decl(til_next) {
+                        return false;
+                    }
+                    if gap <= indent {
+                        return self.nth_tok(til_next).map(|t| &t.tt) != Some(&TokenType::EOF);
+                    }
+                    for _ in 0..til_next {
+                        self.consume_tok();
+                    }
+                }
+                Some(_) => {
+                    self.consume_tok();
+                }
+            }
+        }
+    }
+
+    /// Returns the span from `lo` to the end of the last token consumed, it is
+    /// empty if no token was consumed since `lo`.
+    pub fn span_since(&self, lo: BytePos) -> Span {
+        Span::new(lo, self.last_hi.max(lo))
+    }
+
+    /// Returns true if the token at `idx` is a keyword starting a declaration,
+    /// no statement nor any other element of a block starts with one.
+    pub fn starts_decl(&mut self, idx: usize) -> bool {
+        matches!(
+            self.nth_tok(idx),
+            Some(Token {
+                tt: TokenType::KW(Keyword::Fun | Keyword::Type | Keyword::Import | Keyword::Pub),
+                ..
+            })
+        )
+    }
+
+    /// Returns the name of the function declared by the next tokens, if they
+    /// start a function declaration.
+    pub fn fun_decl_name(&mut self) -> Option<String> {
+        let idx = match self.try_peek_tok()?.tt {
+            TokenType::KW(Keyword::Pub) => 1,
+            _ => 0,
+        };
+        if self.nth_tok(idx)?.tt != TokenType::KW(Keyword::Fun) {
+            return None;
+        }
+        match &self.nth_tok(idx + 1)?.tt {
+            TokenType::Ident(name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the depth of the indent stack and the precedence, to restore
+    /// them after a construct that failed to parse.
+    pub fn recovery_point(&self) -> (usize, PrecedenceValue) {
+        (self.indent.len(), self.current_precedence)
+    }
+
+    /// Restores the indent stack and the precedence saved by
+    /// `recovery_point`.
+    pub fn restore(&mut self, (depth, precedence): (usize, PrecedenceValue)) {
+        self.indent.truncate(depth);
+        self.current_precedence = precedence;
+    }
+
     pub fn indent(&mut self, size: BytePos) {
         self.indent.push(size);
     }
@@ -125,9 +218,15 @@ impl<'r, L: AbsLexer> Parser<'r, L> {
             ws = loc.hi - lf.hi;
         }
 
-        let next = self.nth_tok(idx)?.loc.clone();
+        let next = self.nth_tok(idx)?.clone();
+
+        // the end of file is never indented, and its location may be before
+        // the last new line.
+        if next.tt == TokenType::EOF {
+            return Some((BytePos::ZERO, idx));
+        }
 
-        let gap = next.lo - lf.hi - ws;
+        let gap = next.loc.lo - lf.hi - ws;
         Some((gap, idx))
     }
 }
@@ -136,6 +235,13 @@ pub trait AstNode: fmt::Debug {
     type Output: Location;
 
     fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag>;
+
+    /// Returns the node standing for an element of a block that failed to
+    /// parse, inserted by the error recovery. Without error node the element
+    /// is left out of the block.
+    fn error_node(_loc: Span) -> Option<Self::Output> {
+        None
+    }
 }
 
 pub trait Location {
@@ -239,6 +345,7 @@ macro_rules! expect_token {
                 $parser
                     .dcx()
                     .struct_err($crate::expected_tok_msg(found.tt, $expected), found.loc)
+                    .with_code(E0008)
             );
         })
     )
@@ -291,3 +398,69 @@ fn format_expected<const N: usize>(exptd: [impl Display; N]) -> String {
 
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use rosa_comm::source_map::SourceMap;
+
+    use super::*;
+
+    const UNIT_TEST_PATH: &str = "<unit test>";
+
+    /// Parses the source code and returns the AST and the messages of the
+    /// errors, with their line.
+    fn parse(text: &str) -> (Vec<Declaration>, Vec<(u32, String)>) {
+        let sm = SourceMap::new();
+        let file = sm.add_file(UNIT_TEST_PATH, text);
+        let dcx = DiagCtxt::new(&sm);
+        let ast = Parser::new(BufferedLexer::new(Lexer::new(&file, &dcx))).begin_parsing();
+        let errors = dcx
+            .into_diags()
+            .iter()
+            .map(|d| {
+                let line = file.line_col(d.primary_span().lo).line;
+                (line, d.message().to_string())
+            })
+            .collect();
+        (ast, errors)
+    }
+
+    #[test]
+    fn recovery() {
+        let (_, errors) = parse(
+            "fun a() -> int =\n    let x = )\n    if x:\n        let = 3\n    x\n\nfun b( -> int =\n    1\n\ntype T = object\n    x int\n    y: int\n\nfun c() =\n    1 +* 2\n    fun d() = 1\n",
+        );
+        assert_eq!(
+            errors,
+            [
+                (2, "expected expression, found `)`".to_string()),
+                (4, "expected identifier, found `=`".to_string()),
+                (7, "expected identifier, found `->`".to_string()),
+                (11, "expected :, found identifier `int`".to_string()),
+                (15, "expected expression, found `*`".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_nodes() {
+        let (ast, errors) = parse(
+            "fun a(n: int) -> int =\n    let x = )\n    n +* 2\n    x\n\nfun b( -> int =\n    1\n\nfun c() -> int =\n    a(1) + b()\n",
+        );
+        assert_eq!(errors.len(), 3);
+
+        assert!(matches!(
+            &ast[1].decl,
+            DeclarationInner::Error { name: Some(name) } if name == "b"
+        ));
+        let DeclarationInner::Function { block, .. } = &ast[0].decl else {
+            panic!("not a function");
+        };
+        let StatementInner::LetStmt { value, .. } = &block.content[0].stmt else {
+            panic!("not a let statement");
+        };
+        assert!(matches!(value.expr, ExpressionInner::Error));
+        assert!(matches!(block.content[1].stmt, StatementInner::Error));
+        assert!(matches!(ast[2].decl, DeclarationInner::Function { .. }));
+    }
+}
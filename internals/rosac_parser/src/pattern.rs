@@ -0,0 +1,79 @@
+//! Module responsible for parsing the patterns of the match expressions.
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub pat: PatternInner,
+    pub loc: Span,
+}
+
+derive_loc!(Pattern);
+
+#[derive(Debug, Clone)]
+pub enum PatternInner {
+    /// `_`, matches any value.
+    Wildcard,
+    /// A variant of an enum with its payload bound to new variables, e.g:
+    /// `Rect(w, h)` or `Empty`, the variables come with the location of their
+    /// name.
+    Variant {
+        name: String,
+        bindings: Vec<(Symbol, Span)>,
+    },
+}
+
+impl AstNode for Pattern {
+    type Output = Self;
+
+    fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
+        let (name, Span { lo, mut hi }) =
+            expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+        if name == "_" {
+            return Fuzzy::Ok(Pattern {
+                pat: PatternInner::Wildcard,
+                loc: Span::new(lo, hi),
+            });
+        }
+
+        let mut bindings = Vec::new();
+        if let Some(Token {
+            tt: Punct(Punctuation::LParen),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            expect_token!(parser => [Punct(Punctuation::LParen), ()], [FmtToken::Punct(Punctuation::LParen)]);
+
+            loop {
+                if let Some(Token {
+                    tt: Punct(Punctuation::RParen),
+                    ..
+                }) = parser.try_peek_tok()
+                {
+                    break;
+                }
+
+                let (binding, loc) = expect_token!(parser => [Ident(binding), binding.clone()], [FmtToken::Identifier]);
+                bindings.push((Symbol::new(binding), loc));
+
+                expect_token!(
+                    parser => [
+                        Punct(Punctuation::Comma), (); Punct(Punctuation::RParen), (), in break
+                    ],
+                    [FmtToken::Punct(Punctuation::Comma), FmtToken::Punct(Punctuation::RParen)]
+                );
+            }
+
+            (_, Span { hi, .. }) = expect_token!(
+                parser => [Punct(Punctuation::RParen), ()],
+                [FmtToken::Punct(Punctuation::RParen)]
+            );
+        }
+
+        Fuzzy::Ok(Pattern {
+            pat: PatternInner::Variant { name, bindings },
+            loc: Span::new(lo, hi),
+        })
+    }
+}
@@ -11,11 +11,14 @@ pub use crate::precedence::{operator_precedence, PrecedenceValue};
 
 // Main AST node of each module
 pub use crate::block::Block;
-pub use crate::decl::{Declaration, DeclarationInner, Visibility};
-pub use crate::expr::{Associativity, Expression, ExpressionInner, Operator};
+pub use crate::decl::{Arg, Declaration, DeclarationInner, Field, Variant, Visibility};
+pub use crate::expr::{
+    Associativity, BinaryOp, Expression, ExpressionInner, MatchArm, Operator, UnaryOp,
+};
+pub use crate::pattern::{Pattern, PatternInner};
 pub use crate::stmt::{Statement, StatementInner};
 pub use crate::symbol::{Symbol, SymbolKind};
-pub use crate::types::{Type, TypeInner};
+pub use crate::types::{FnPtrArg, Type, TypeInner};
 
 // Other crates preludes
 pub(crate) use rosa_comm::prelude::*;
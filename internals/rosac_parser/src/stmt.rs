@@ -16,17 +16,33 @@ impl AstNode for Statement {
     fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
         StatementInner::parse(parser)
     }
+
+    fn error_node(loc: Span) -> Option<Self::Output> {
+        Some(Statement {
+            stmt: StatementInner::Error,
+            loc,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum StatementInner {
-    IfStmt {
-        predicate: Expression,
-        body: Block<Statement>,
-        else_branch: Option<Block<Statement>>,
+    LetStmt {
+        symbol: Symbol,
+        name_loc: Span,
+        mutable: bool,
+        ty: Option<Type>,
+        value: Expression,
     },
     ExprStmt(Expression),
+    /// Assignment of a value to a place, e.g: `out = 3 * in`
+    AssignStmt {
+        place: Expression,
+        value: Expression,
+    },
     ReturnStmt(Option<Expression>),
+    /// A statement that failed to parse, inserted by the error recovery.
+    Error,
 }
 
 impl AstNode for StatementInner {
@@ -35,9 +51,9 @@ impl AstNode for StatementInner {
     fn parse<L: AbsLexer>(parser: &mut Parser<'_, L>) -> Fuzzy<Self::Output, Diag> {
         match parser.peek_tok() {
             Token {
-                tt: KW(Keyword::If),
+                tt: KW(Keyword::Let),
                 ..
-            } => parse_if_stmt(parser),
+            } => parse_let_stmt(parser),
             Token {
                 tt: KW(Keyword::Return),
                 ..
@@ -51,50 +67,63 @@ pub fn parse_expr_stmt(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Statemen
     let expr = parse!(parser => Expression);
     // TODO: try to improve the errors, here when parsing of expression fails
     // it says 'expected expression, found ..'
+
+    if let Some(Token {
+        tt: Punct(Punctuation::Equal),
+        ..
+    }) = parser.try_peek_tok()
+    {
+        expect_token!(parser => [Punct(Punctuation::Equal), ()], [FmtToken::Punct(Punctuation::Equal)]);
+        let value = parse_stmt_value(parser);
+
+        return Fuzzy::Ok(Statement {
+            loc: Span::new(expr.loc.lo, value.loc.hi),
+            stmt: StatementInner::AssignStmt { place: expr, value },
+        });
+    }
+
     Fuzzy::Ok(Statement {
         loc: expr.loc.clone(),
         stmt: StatementInner::ExprStmt(expr),
     })
 }
 
-pub fn parse_if_stmt(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Statement, Diag> {
+pub fn parse_let_stmt(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Statement, Diag> {
     let (_, Span { lo, .. }) =
-        expect_token!(parser => [KW(Keyword::If), ()], [FmtToken::KW(Keyword::If)]);
-    let predicate = parse!(parser => Expression);
+        expect_token!(parser => [KW(Keyword::Let), ()], [FmtToken::KW(Keyword::Let)]);
 
-    expect_token!(
-        parser => [Punct(Punctuation::Colon), ()],
-        [FmtToken::Punct(Punctuation::Colon)]
+    let (mutable, _) = expect_token!(
+        parser => [KW(Keyword::Mut), true]
+        else { (false, Span::ZERO) }
     );
-    let body = parse!(parser => Block<Statement>);
-    let mut hi = body.loc.hi;
 
-    let else_branch = if let Some(Token {
-        tt: KW(Keyword::Else),
+    let (name, name_loc) =
+        expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+    // the type annotation is optional, without it the type is inferred.
+    let ty = if let Some(Token {
+        tt: Punct(Punctuation::Colon),
         ..
     }) = parser.try_peek_tok()
     {
-        expect_token!(parser => [KW(Keyword::Else), ()], [FmtToken::KW(Keyword::Else)]);
-
-        expect_token!(
-            parser => [Punct(Punctuation::Colon), ()],
-            [FmtToken::Punct(Punctuation::Colon)]
-        );
-
-        let r#else = parse!(parser => Block<Statement>);
-        hi = r#else.loc.hi;
-        Some(r#else)
+        expect_token!(parser => [Punct(Punctuation::Colon), ()], [FmtToken::Punct(Punctuation::Colon)]);
+        Some(parse!(parser => Type))
     } else {
         None
     };
 
+    expect_token!(parser => [Punct(Punctuation::Equal), ()], [FmtToken::Punct(Punctuation::Equal)]);
+    let value = parse_stmt_value(parser);
+
     Fuzzy::Ok(Statement {
-        stmt: StatementInner::IfStmt {
-            predicate,
-            body,
-            else_branch,
+        loc: Span::new(lo, value.loc.hi),
+        stmt: StatementInner::LetStmt {
+            symbol: Symbol::new(name),
+            name_loc,
+            mutable,
+            ty,
+            value,
         },
-        loc: Span::new(lo, hi),
     })
 }
 
@@ -109,7 +138,7 @@ pub fn parse_return_stmt(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Statem
         });
     }
 
-    let expr = parse!(parser => Expression);
+    let expr = parse_stmt_value(parser);
     loc = Span::from_ends(loc, expr.loc.clone());
 
     Fuzzy::Ok(Statement {
@@ -117,3 +146,43 @@ pub fn parse_return_stmt(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Statem
         loc,
     })
 }
+
+/// Parses the value ending a let, an assignment or a return statement. When
+/// it fails to parse, the error is emitted, the rest of the statement is
+/// skipped and an error expression stands for the value, so the statement is
+/// kept, e.g: the variable of a let statement is still defined.
+pub fn parse_stmt_value(parser: &mut Parser<'_, impl AbsLexer>) -> Expression {
+    let lo = parser.peek_tok().loc.lo;
+    let recovery = parser.recovery_point();
+    match Expression::parse(parser) {
+        Fuzzy::Ok(expr) => expr,
+        Fuzzy::Fuzzy(expr, diags) => {
+            parser.dcx().emit_diags(diags);
+            expr
+        }
+        Fuzzy::Err(diag) => {
+            parser.dcx().emit_diag(diag);
+            parser.restore(recovery);
+            let indent = parser.last_indent().unwrap_or(BytePos::ZERO);
+            parser.synchronize(indent);
+            Expression {
+                expr: ExpressionInner::Error,
+                loc: parser.span_since(lo),
+            }
+        }
+    }
+}
+
+impl Block<Statement> {
+    /// Returns the expression giving its value to the block, it's the last
+    /// statement of the block if it is an expression statement.
+    pub fn value(&self) -> Option<&Expression> {
+        match self.content.last() {
+            Some(Statement {
+                stmt: StatementInner::ExprStmt(expr),
+                ..
+            }) => Some(expr),
+            _ => None,
+        }
+    }
+}
@@ -6,10 +6,19 @@ use crate::prelude::*;
 pub enum SymbolKind {
     /// Argument of a function
     Arg,
+    /// `var` argument of a function, it is passed by reference so it may be
+    /// assigned
+    VarArg,
     /// Local variable
     Local,
+    /// Mutable local variable, defined with `let mut`
+    MutLocal,
     /// Global variable
     Global,
+    /// Variant of an enum, used to construct the values of the enum
+    Variant,
+    /// Built-in function, like `len`
+    Builtin,
 }
 
 #[derive(Debug, Clone)]
@@ -22,8 +31,9 @@ pub enum SymbolInner {
     Defined {
         name: String,
         kind: SymbolKind,
-        // TODO: make `ty` optional so the type of variables can be inferred.
-        ty: Type,
+        /// `None` if the type of the symbol has to be inferred, it is then
+        /// set by the type checker.
+        ty: Option<Type>,
         which: u32,
     },
 }
@@ -40,9 +50,16 @@ impl Symbol {
         }
     }
 
+    /// Returns the name of the symbol.
+    pub fn name(&self) -> String {
+        match &*self.s.borrow() {
+            SymbolInner::Undefined(name) | SymbolInner::Defined { name, .. } => name.clone(),
+        }
+    }
+
     /// Transforms an Undefined symbol to a Defined one, if it is already
     /// defined, does nothing
-    pub fn define(&self, kind: SymbolKind, ty: Type, which: u32) {
+    pub fn define(&self, kind: SymbolKind, ty: Option<Type>, which: u32) {
         let name = match &*self.s.borrow() {
             SymbolInner::Undefined(name) => name.clone(),
             SymbolInner::Defined { .. } => return,
@@ -56,7 +73,15 @@ impl Symbol {
         }
     }
 
-    pub fn new_def(name: String, kind: SymbolKind, ty: Type, which: u32) -> Symbol {
+    /// Sets the type of a defined symbol whose type had to be inferred, does
+    /// nothing if the symbol is undefined.
+    pub fn set_inferred_ty(&self, inferred: Type) {
+        if let SymbolInner::Defined { ty, .. } = &mut *self.s.borrow_mut() {
+            *ty = Some(inferred);
+        }
+    }
+
+    pub fn new_def(name: String, kind: SymbolKind, ty: Option<Type>, which: u32) -> Symbol {
         Symbol {
             s: RefCell::new(SymbolInner::Defined {
                 name,
@@ -1,6 +1,10 @@
+use std::{cell::RefCell, fmt::Display, ops::RangeInclusive};
+
+use rosa_comm::edit_distance::find_best_match;
+
 use crate::prelude::*;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeInner {
     // unsigned integers
     UInt8,
@@ -17,35 +21,139 @@ pub enum TypeInner {
 
     Bool,
     Char,
-    // String,
+    String,
+    /// A fixed-size array, e.g: `[int; 3]`
+    Array {
+        elem: Box<Type>,
+        len: u64,
+    },
+    /// A view into a sequence of values, e.g: `[int]`
+    Slice(Box<Type>),
+    /// A type declared in the source code, like an object, refered to by its
+    /// name.
+    Named {
+        name: String,
+        /// Index of the module declaring the type, the types with the same
+        /// name declared in different modules are different. It is `None`
+        /// until it's set by the name resolution of the semantic analyzer.
+        module: RefCell<Option<usize>>,
+    },
+
+    /// The type of the expressions that don't produce any value, like a call
+    /// to a function without a return type.
+    Void,
 
-    // TODO: implement parsing for function pointers
-    // e.g: `fun (int, bool) -> int` is a fn ptr
-    // like `fun ()` is also a fn ptr
+    /// An integer type that isn't known yet. It is never produced by the
+    /// parser, it's used by the semantic analyzer to infer the type of integer
+    /// literals.
+    IntVar(u32),
+
+    /// A function pointer, e.g: `fun (int, var bool) -> int` or `fun ()`
     FnPtr {
-        args: Vec<Type>,
+        args: Vec<FnPtrArg>,
         ret: Option<Box<Type>>,
     },
 }
 
 impl TypeInner {
-    pub fn is_primitive_type(ty: &str) -> bool {
+    /// Is the type an integer type, signed or not
+    pub fn is_integer(&self) -> bool {
+        self.is_signed() || self.is_unsigned() || matches!(self, Self::IntVar(_))
+    }
+
+    /// Is the type a signed integer type
+    pub fn is_signed(&self) -> bool {
         matches!(
-            ty,
-            "uint8"
-                | "uint16"
-                | "uint32"
-                | "uint64"
-                | "uint"
-                | "int8"
-                | "int16"
-                | "int32"
-                | "int64"
-                | "int"
-                | "bool"
-                | "char"
+            self,
+            Self::Int8 | Self::Int16 | Self::Int32 | Self::Int64 | Self::Int
         )
     }
+
+    /// Is the type an unsigned integer type
+    pub fn is_unsigned(&self) -> bool {
+        matches!(
+            self,
+            Self::UInt8 | Self::UInt16 | Self::UInt32 | Self::UInt64 | Self::UInt
+        )
+    }
+
+    /// Returns the range of the values an integer of this type can hold,
+    /// `None` if it's not an integer type or if it is an integer variable.
+    ///
+    /// `int` and `uint` are 64 bits wide.
+    pub fn int_range(&self) -> Option<RangeInclusive<i128>> {
+        Some(match self {
+            Self::UInt8 => 0..=u8::MAX as i128,
+            Self::UInt16 => 0..=u16::MAX as i128,
+            Self::UInt32 => 0..=u32::MAX as i128,
+            Self::UInt64 | Self::UInt => 0..=u64::MAX as i128,
+            Self::Int8 => i8::MIN as i128..=i8::MAX as i128,
+            Self::Int16 => i16::MIN as i128..=i16::MAX as i128,
+            Self::Int32 => i32::MIN as i128..=i32::MAX as i128,
+            Self::Int64 | Self::Int => i64::MIN as i128..=i64::MAX as i128,
+            _ => return None,
+        })
+    }
+
+    /// The names of the primitive types.
+    pub const PRIMITIVE_TYPES: [&'static str; 13] = [
+        "uint8", "uint16", "uint32", "uint64", "uint", "int8", "int16", "int32", "int64", "int",
+        "bool", "char", "String",
+    ];
+
+    pub fn is_primitive_type(ty: &str) -> bool {
+        TypeInner::PRIMITIVE_TYPES.contains(&ty)
+    }
+}
+
+impl Display for TypeInner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UInt8 => write!(f, "uint8"),
+            Self::UInt16 => write!(f, "uint16"),
+            Self::UInt32 => write!(f, "uint32"),
+            Self::UInt64 => write!(f, "uint64"),
+            Self::UInt => write!(f, "uint"),
+            Self::Int8 => write!(f, "int8"),
+            Self::Int16 => write!(f, "int16"),
+            Self::Int32 => write!(f, "int32"),
+            Self::Int64 => write!(f, "int64"),
+            Self::Int => write!(f, "int"),
+            Self::Bool => write!(f, "bool"),
+            Self::Char => write!(f, "char"),
+            Self::String => write!(f, "String"),
+            Self::Array { elem, len } => write!(f, "[{}; {len}]", elem.ty),
+            Self::Slice(elem) => write!(f, "[{}]", elem.ty),
+            Self::Named { name, .. } => write!(f, "{name}"),
+            Self::Void => write!(f, "void"),
+            Self::IntVar(_) => write!(f, "{{integer}}"),
+            Self::FnPtr { args, ret } => {
+                write!(f, "fun (")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    if arg.var {
+                        write!(f, "var ")?;
+                    }
+                    write!(f, "{}", arg.ty.ty)?;
+                }
+                write!(f, ")")?;
+                if let Some(ret) = ret {
+                    write!(f, " -> {}", ret.ty)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An argument of a function pointer type, the `var` arguments are passed by
+/// reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnPtrArg {
+    pub ty: Type,
+    pub var: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +164,13 @@ pub struct Type {
 
 derive_loc!(Type);
 
+impl PartialEq for Type {
+    fn eq(&self, other: &Self) -> bool {
+        // the location doesn't matter when comparing two types.
+        self.ty == other.ty
+    }
+}
+
 impl AstNode for Type {
     type Output = Self;
 
@@ -64,12 +179,22 @@ impl AstNode for Type {
             Token {
                 tt: Ident(name), ..
             } if TypeInner::is_primitive_type(name) => parse_primitive_type(parser),
+            Token { tt: Ident(_), .. } => parse_named_type(parser),
+            Token {
+                tt: KW(Keyword::Fun),
+                ..
+            } => parse_fn_ptr_type(parser),
+            Token {
+                tt: Punct(Punctuation::LBracket),
+                ..
+            } => parse_array_type(parser),
             t => {
                 let tok = t.clone();
                 Fuzzy::Err(
                     parser
                         .dcx()
-                        .struct_err(expected_tok_msg(tok.tt, [AstPart::Type]), tok.loc),
+                        .struct_err(expected_tok_msg(tok.tt, [AstPart::Type]), tok.loc)
+                        .with_code(E0008),
                 )
             }
         }
@@ -95,13 +220,127 @@ pub fn parse_primitive_type(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Typ
 
         "bool" => TypeInner::Bool,
         "char" => TypeInner::Char,
+        "String" => TypeInner::String,
         _ => {
-            return Fuzzy::Err(parser.dcx().struct_err(
-                expected_tok_msg(FmtToken::NamedIdentifier(ty_str), ["primitive type"]),
-                loc,
-            ))
+            let similar = find_best_match(&ty_str, TypeInner::PRIMITIVE_TYPES);
+            let mut diag = parser
+                .dcx()
+                .struct_err(
+                    expected_tok_msg(FmtToken::NamedIdentifier(ty_str), ["primitive type"]),
+                    loc.clone(),
+                )
+                .with_code(E0008);
+            if let Some(similar) = similar {
+                diag = diag.with_suggestion(
+                    loc,
+                    "a primitive type with a similar name exists",
+                    similar,
+                    Applicability::MaybeIncorrect,
+                );
+            }
+            return Fuzzy::Err(diag);
         }
     };
 
     Fuzzy::Ok(Type { ty, loc })
 }
+
+pub fn parse_named_type(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Type, Diag> {
+    let (name, loc) = expect_token!(parser => [Ident(name), name.clone()], [FmtToken::Identifier]);
+
+    Fuzzy::Ok(Type {
+        ty: TypeInner::Named {
+            name,
+            module: RefCell::new(None),
+        },
+        loc,
+    })
+}
+
+pub fn parse_fn_ptr_type(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Type, Diag> {
+    let (_, Span { lo, .. }) =
+        expect_token!(parser => [KW(Keyword::Fun), ()], [FmtToken::KW(Keyword::Fun)]);
+
+    expect_token!(parser => [Punct(Punctuation::LParen), ()], [FmtToken::Punct(Punctuation::LParen)]);
+
+    let mut args = Vec::new();
+    loop {
+        if let Some(Token {
+            tt: Punct(Punctuation::RParen),
+            ..
+        }) = parser.try_peek_tok()
+        {
+            break;
+        }
+
+        let (var, _) = expect_token!(
+            parser => [KW(Keyword::Var), true]
+            else { (false, Span::ZERO) }
+        );
+        let ty = parse!(parser => Type);
+        args.push(FnPtrArg { ty, var });
+
+        expect_token!(
+            parser => [
+                Punct(Punctuation::Comma), (); Punct(Punctuation::RParen), (), in break
+            ],
+            [FmtToken::Punct(Punctuation::Comma), FmtToken::Punct(Punctuation::RParen)]
+        );
+    }
+
+    let (_, Span { mut hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::RParen), ()],
+        [FmtToken::Punct(Punctuation::RParen)]
+    );
+
+    let ret = if let Some(Token {
+        tt: Punct(Punctuation::ThinRArrow),
+        ..
+    }) = parser.try_peek_tok()
+    {
+        expect_token!(parser => [Punct(Punctuation::ThinRArrow), ()], [FmtToken::Punct(Punctuation::ThinRArrow)]);
+        let ret = parse!(parser => Type);
+        hi = ret.loc.hi;
+        Some(Box::new(ret))
+    } else {
+        None
+    };
+
+    Fuzzy::Ok(Type {
+        ty: TypeInner::FnPtr { args, ret },
+        loc: Span::new(lo, hi),
+    })
+}
+
+/// Parses an array type, `[int; 3]`, or a slice type, `[int]`.
+pub fn parse_array_type(parser: &mut Parser<'_, impl AbsLexer>) -> Fuzzy<Type, Diag> {
+    let (_, Span { lo, .. }) = expect_token!(
+        parser => [Punct(Punctuation::LBracket), ()],
+        [FmtToken::Punct(Punctuation::LBracket)]
+    );
+
+    let elem = Box::new(parse!(parser => Type));
+
+    let (is_array, Span { mut hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::Semi), true; Punct(Punctuation::RBracket), false],
+        [FmtToken::Punct(Punctuation::Semi), FmtToken::Punct(Punctuation::RBracket)]
+    );
+    if !is_array {
+        return Fuzzy::Ok(Type {
+            ty: TypeInner::Slice(elem),
+            loc: Span::new(lo, hi),
+        });
+    }
+
+    let (len, _) = expect_token!(parser => [Int(len), *len], [FmtToken::IntLiteral]);
+
+    (_, Span { hi, .. }) = expect_token!(
+        parser => [Punct(Punctuation::RBracket), ()],
+        [FmtToken::Punct(Punctuation::RBracket)]
+    );
+
+    Fuzzy::Ok(Type {
+        ty: TypeInner::Array { elem, len },
+        loc: Span::new(lo, hi),
+    })
+}
@@ -0,0 +1,71 @@
+//! Module responsible for the built-in functions, they are the functions
+//! that cannot be written in Rosa, like `len` that takes any array or slice.
+
+use crate::prelude::*;
+
+/// The names of the built-in functions. They are resolved after the symbols
+/// of the module and of the imported modules, so a function may be declared
+/// with the name of a built-in one.
+pub const BUILTINS: &[&str] = &["len"];
+
+impl<'r> SemanticAnalyzer<'r> {
+    /// Defines the symbol as the built-in function with its name, returns
+    /// false if there is no built-in function with this name.
+    pub fn define_builtin(&self, symbol: &Symbol) -> bool {
+        let name = symbol.name();
+        match BUILTINS.iter().position(|builtin| *builtin == name) {
+            Some(i) => {
+                symbol.define(SymbolKind::Builtin, None, i as u32);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Type checks the call to the built-in function `name`.
+    pub fn type_of_builtin_call(
+        &mut self,
+        expr: &'r Expression,
+        name: &str,
+        args: &'r [Expression],
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        match name {
+            "len" => {
+                if args.len() != 1 {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!(
+                                    "this function takes 1 argument but {} {} supplied",
+                                    args.len(),
+                                    if args.len() == 1 { "was" } else { "were" },
+                                ),
+                                expr.loc.clone(),
+                            )
+                            .with_code(E0024),
+                    );
+                    for arg in args {
+                        self.type_of_expr(arg, None, diags);
+                    }
+                    return Some(TypeInner::UInt);
+                }
+
+                let ty = self.type_of_expr(&args[0], None, diags)?;
+                let ty = self.infcx.resolve(&ty);
+                if !matches!(ty, TypeInner::Array { .. } | TypeInner::Slice(_)) {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!("expected an array or a slice, found `{ty}`"),
+                                args[0].loc.clone(),
+                            )
+                            .with_code(E0019),
+                    );
+                }
+                Some(TypeInner::UInt)
+            }
+            _ => unreachable!("unknown built-in function `{name}`"),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Module responsible for the type inference, it provides the inference
+//! context used by the type checker to infer the type of integer literals and
+//! of the let statements without a type annotation.
+//!
+//! # Type Inference
+//!
+//! An integer literal with no known type gets a new integer variable
+//! ([`TypeInner::IntVar`]) as type. The variable is then unified with the types
+//! it meets, e.g: when passed as an argument to a function, and if it was
+//! never unified at the end of the function, it defaults to `int`.
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Default)]
+pub struct InferCtxt {
+    /// The type each integer variable is bound to, `None` if it's not bound.
+    /// The index is the number of the integer variable.
+    int_vars: Vec<Option<TypeInner>>,
+}
+
+impl InferCtxt {
+    pub fn new() -> InferCtxt {
+        InferCtxt {
+            int_vars: Vec::new(),
+        }
+    }
+
+    /// Creates a new integer variable, not bound to any type.
+    pub fn new_int_var(&mut self) -> TypeInner {
+        self.int_vars.push(None);
+        TypeInner::IntVar(self.int_vars.len() as u32 - 1)
+    }
+
+    /// Returns the type behind the integer variables, also in the element
+    /// type of arrays and slices. If the type is not an integer variable or an
+    /// unbound one, it is returned as is.
+    pub fn resolve(&self, ty: &TypeInner) -> TypeInner {
+        self.map_int_vars(ty, &|var| var)
+    }
+
+    /// Resolves the type and calls `unbound` on the integer variables that
+    /// are not bound.
+    fn map_int_vars(&self, ty: &TypeInner, unbound: &dyn Fn(TypeInner) -> TypeInner) -> TypeInner {
+        match ty {
+            TypeInner::IntVar(var) => match &self.int_vars[*var as usize] {
+                Some(bound) => self.map_int_vars(bound, unbound),
+                None => unbound(ty.clone()),
+            },
+            TypeInner::Array { elem, len } => TypeInner::Array {
+                elem: Box::new(Type {
+                    ty: self.map_int_vars(&elem.ty, unbound),
+                    loc: elem.loc.clone(),
+                }),
+                len: *len,
+            },
+            TypeInner::Slice(elem) => TypeInner::Slice(Box::new(Type {
+                ty: self.map_int_vars(&elem.ty, unbound),
+                loc: elem.loc.clone(),
+            })),
+            _ => ty.clone(),
+        }
+    }
+
+    /// Tries to make the two types equal by binding the integer variables,
+    /// returns false if it is impossible.
+    pub fn unify(&mut self, a: &TypeInner, b: &TypeInner) -> bool {
+        let (a, b) = (self.resolve(a), self.resolve(b));
+        match (&a, &b) {
+            _ if a == b => true,
+            (TypeInner::IntVar(var), other) | (other, TypeInner::IntVar(var))
+                if other.is_integer() =>
+            {
+                self.int_vars[*var as usize] = Some(other.clone());
+                true
+            }
+            (
+                TypeInner::Array { elem, len },
+                TypeInner::Array {
+                    elem: other,
+                    len: other_len,
+                },
+            ) if len == other_len => self.unify(&elem.ty, &other.ty),
+            (TypeInner::Slice(elem), TypeInner::Slice(other)) => self.unify(&elem.ty, &other.ty),
+            _ => false,
+        }
+    }
+
+    /// Like [`InferCtxt::resolve`] but an unbound integer variable defaults
+    /// to `int`.
+    pub fn resolve_or_default(&self, ty: &TypeInner) -> TypeInner {
+        self.map_int_vars(ty, &|_| TypeInner::Int)
+    }
+
+    /// Forgets all the integer variables, it must be called only when the
+    /// inferred types are no longer needed.
+    pub fn clear(&mut self) {
+        self.int_vars.clear();
+    }
+}
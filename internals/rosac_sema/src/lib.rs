@@ -1,11 +1,18 @@
 //! The semantic analyzer crate, it is responsible of analyzing the semantics
 //! of the AST.
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::infer::InferCtxt;
+use crate::lint::LintLevels;
 use crate::prelude::*;
 
+pub mod builtin;
+pub mod infer;
+pub mod lint;
+pub mod module;
 pub mod name;
 pub mod prelude;
+pub mod typeck;
 
 /// Symbol Table Error.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,6 +27,10 @@ pub enum SymTabError {
 pub struct SymbolTable {
     // TODO: maybe refactor 'HashMap<String, Symbol>' to its type
     stack: Vec<HashMap<String, Symbol>>,
+    /// Locations of the definitions of the symbols, by scope like `stack`.
+    def_locs: Vec<HashMap<String, Span>>,
+    /// Names of the symbols used, by scope like `stack`.
+    used: Vec<HashSet<String>>,
 }
 
 impl SymbolTable {
@@ -27,6 +38,8 @@ impl SymbolTable {
     pub fn new() -> SymbolTable {
         SymbolTable {
             stack: vec![HashMap::new()],
+            def_locs: vec![HashMap::new()],
+            used: vec![HashSet::new()],
         }
     }
 
@@ -47,6 +60,8 @@ impl SymbolTable {
     /// Enter a new scope, in fact create a new HashMap
     pub fn scope_enter(&mut self) {
         self.stack.push(HashMap::new());
+        self.def_locs.push(HashMap::new());
+        self.used.push(HashSet::new());
     }
 
     /// Exit a the top most scope, in fact dropping the top most HashMap
@@ -57,6 +72,8 @@ impl SymbolTable {
         // we are sure it will not panic because the Global scope may not be
         // exited.
         self.stack.pop().unwrap();
+        self.def_locs.pop().unwrap();
+        self.used.pop().unwrap();
         Ok(())
     }
 
@@ -76,6 +93,94 @@ impl SymbolTable {
         Ok(())
     }
 
+    /// Like `scope_bind` but also records the location of the definition of
+    /// the symbol.
+    pub fn scope_bind_at(
+        &mut self,
+        name: String,
+        sym: Symbol,
+        loc: Span,
+    ) -> Result<(), SymTabError> {
+        self.scope_bind(name.clone(), sym)?;
+        self.def_locs.last_mut().unwrap().insert(name, loc);
+        Ok(())
+    }
+
+    /// Like `scope_bind_at` but the symbol may shadow a symbol of an enclosing
+    /// scope, only a symbol of the top most scope with the same name is an
+    /// error.
+    pub fn scope_shadow_at(
+        &mut self,
+        name: String,
+        sym: Symbol,
+        loc: Span,
+    ) -> Result<(), SymTabError> {
+        if self.scope_lookup_top(&name).is_some() {
+            return Err(SymTabError::ShadowSymbol);
+        }
+        self.top_most_mut().insert(name.clone(), sym);
+        self.def_locs.last_mut().unwrap().insert(name, loc);
+        Ok(())
+    }
+
+    /// Marks the symbol visible with this name as used.
+    pub fn mark_used(&mut self, name: &str) {
+        if let Some(i) = self
+            .stack
+            .iter()
+            .rposition(|scope| scope.contains_key(name))
+        {
+            self.used[i].insert(name.to_string());
+        }
+    }
+
+    /// Returns true if the symbol visible with this name was used.
+    pub fn is_used(&self, name: &str) -> bool {
+        match self
+            .stack
+            .iter()
+            .rposition(|scope| scope.contains_key(name))
+        {
+            Some(i) => self.used[i].contains(name),
+            None => false,
+        }
+    }
+
+    /// Returns the names of the symbols of the top most scope that were never
+    /// used, with the location of their definition, in the order of their
+    /// definition.
+    pub fn unused_top(&self) -> Vec<(&str, &Span)> {
+        let used = self.used.last().unwrap();
+        let mut unused = self
+            .def_locs
+            .last()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| !used.contains(*name))
+            .map(|(name, loc)| (name.as_str(), loc))
+            .collect::<Vec<_>>();
+        unused.sort_by_key(|(_, loc)| loc.lo);
+        unused
+    }
+
+    /// Returns the location of the definition of the symbol visible with this
+    /// name, if it was recorded.
+    pub fn def_loc(&self, name: &str) -> Option<&Span> {
+        for (scope, locs) in self.stack.iter().zip(&self.def_locs).rev() {
+            if scope.contains_key(name) {
+                return locs.get(name);
+            }
+        }
+        None
+    }
+
+    /// Returns the names of the symbols of all the scopes.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.stack
+            .iter()
+            .flat_map(|scope| scope.keys().map(String::as_str))
+    }
+
     /// Search a symbol from the top most scope to the global scope.
     pub fn scope_lookup(&self, name: &str) -> Option<&Symbol> {
         // we reverse the iterator because we search from the top most to the
@@ -106,24 +211,188 @@ impl Default for SymbolTable {
     }
 }
 
-/// Semantic analyzer of Rosa. It takes a mutable reference to the ast, and
-/// modifies it in the name resolution stage.
+/// The global symbols and types of an analyzed module with their
+/// declaration, they are visible in the modules importing it if they are
+/// public.
+#[derive(Debug, Clone)]
+pub struct ModuleExports<'r> {
+    /// Name of the module, e.g: `std/io`
+    pub module: String,
+    /// Index of the module in the module graph.
+    pub id: usize,
+    pub symbols: HashMap<String, (Symbol, &'r Declaration)>,
+    pub types: HashMap<String, &'r Declaration>,
+}
+
+/// Result of the search of a name in the imported modules.
+#[derive(Debug)]
+pub enum Imported<'a, 'r, T> {
+    NotFound,
+    Public(T),
+    /// The name is only declared privately, in the module and the declaration.
+    Private(&'a ModuleExports<'r>, &'r Declaration),
+    /// The name is declared publicly in several modules.
+    Ambiguous(Vec<&'a str>),
+}
+
+/// Semantic analyzer of Rosa. It takes a reference to the ast of a module, and
+/// defines its symbols in the name resolution stage.
 #[derive(Debug, Clone)]
 pub struct SemanticAnalyzer<'r> {
     table: SymbolTable,
     ast: &'r Vec<Declaration>,
+    /// Index of the analyzed module in the module graph, the named types
+    /// declared in it are resolved to it.
+    module: usize,
     dcx: &'r DiagCtxt<'r>,
     /// Counter used to set the 'which' field of decl's Symbols
     decl_counter: u32,
+    /// Counter used to set the 'which' field of local variables' Symbols, it
+    /// is reset at the start of each function.
+    local_counter: u32,
+    /// Return type of the function being type checked.
+    fun_ret: TypeInner,
+    /// Inference context used by the type checker.
+    infcx: InferCtxt,
+    /// Types of the local variables without type annotation of the function
+    /// being type checked, the key is the 'which' field of their Symbol.
+    local_tys: HashMap<u32, TypeInner>,
+    /// Symbols of the local variables whose type is inferred, in which the
+    /// type is written at the end of the function.
+    inferred_syms: Vec<&'r Symbol>,
+    /// Integer literals of the function being type checked, with their type
+    /// and the expression negating them if any, they are range checked at the
+    /// end of the function.
+    int_lits: Vec<(&'r Expression, TypeInner, Option<&'r Expression>)>,
+    /// Negations of the function being type checked whose operand is an
+    /// integer of a type not known yet, with its type, they are checked at
+    /// the end of the function.
+    negations: Vec<(&'r Expression, TypeInner)>,
+    /// The types declared in the source code, by name.
+    types: HashMap<String, &'r Declaration>,
+    /// The exports of the modules imported by the analyzed module.
+    imports: Vec<ModuleExports<'r>>,
+    /// The levels of the lints reported.
+    lint_levels: LintLevels,
+    /// Whether an error node of the parser was met in the module, and in the
+    /// function being resolved. The unused symbols are not reported after
+    /// one, they may be used by the code that failed to parse.
+    has_error_nodes: bool,
+    fun_has_error_nodes: bool,
 }
 
 impl<'r> SemanticAnalyzer<'r> {
-    pub fn new(ast: &'r mut Vec<Declaration>, dcx: &'r DiagCtxt) -> SemanticAnalyzer<'r> {
+    pub fn new(ast: &'r Vec<Declaration>, dcx: &'r DiagCtxt) -> SemanticAnalyzer<'r> {
         SemanticAnalyzer {
             table: Default::default(),
             ast,
             dcx,
+            module: 0,
             decl_counter: 0,
+            local_counter: 0,
+            fun_ret: TypeInner::Void,
+            infcx: InferCtxt::new(),
+            local_tys: HashMap::new(),
+            inferred_syms: Vec::new(),
+            int_lits: Vec::new(),
+            negations: Vec::new(),
+            types: HashMap::new(),
+            imports: Vec::new(),
+            lint_levels: LintLevels::new(),
+            has_error_nodes: false,
+            fun_has_error_nodes: false,
+        }
+    }
+
+    /// Sets the index of the analyzed module in the module graph, it must be
+    /// called before the analysis.
+    pub fn set_module_id(&mut self, id: usize) {
+        self.module = id;
+    }
+
+    /// Makes the symbols and types exported by a module visible in the
+    /// analyzed module, it must be called before the analysis.
+    pub fn import(&mut self, exports: ModuleExports<'r>) {
+        self.imports.push(exports);
+    }
+
+    /// Returns the global symbols and types of the analyzed module, it must
+    /// be called after the analysis.
+    pub fn exports(&self, module: impl Into<String>) -> ModuleExports<'r> {
+        let mut symbols = HashMap::new();
+        for decl in self.ast {
+            let names = match &decl.decl {
+                DeclarationInner::Function { name, .. } => vec![name],
+                DeclarationInner::Enum { variants, .. } => {
+                    variants.iter().map(|v| &v.name).collect()
+                }
+                _ => continue,
+            };
+            for name in names {
+                if let Some(sym) = self.table.scope_lookup_top(name) {
+                    symbols
+                        .entry(name.clone())
+                        .or_insert_with(|| (sym.clone(), decl));
+                }
+            }
+        }
+
+        ModuleExports {
+            module: module.into(),
+            id: self.module,
+            symbols,
+            types: self.types.clone(),
+        }
+    }
+
+    /// Search the type declaration in the analyzed module and then in the
+    /// imported ones, with the index of the module declaring it.
+    pub fn lookup_type<'a>(&'a self, name: &str) -> Imported<'a, 'r, (usize, &'r Declaration)> {
+        if let Some(decl) = self.types.get(name) {
+            return Imported::Public((self.module, *decl));
+        }
+        self.lookup_imported(|i| i.types.get(name).map(|decl| ((i.id, *decl), *decl)))
+    }
+
+    /// Returns the declaration of the type `name` of the module `module`, it
+    /// must be the analyzed module or an imported one.
+    pub fn type_decl(&self, module: usize, name: &str) -> Option<&'r Declaration> {
+        let types = if module == self.module {
+            &self.types
+        } else {
+            &self.imports.iter().find(|i| i.id == module)?.types
+        };
+        types.get(name).copied()
+    }
+
+    /// Search the symbol in the imported modules.
+    pub fn lookup_import<'a>(&'a self, name: &str) -> Imported<'a, 'r, &'a Symbol> {
+        self.lookup_imported(|i| i.symbols.get(name).map(|(sym, decl)| (sym, *decl)))
+    }
+
+    fn lookup_imported<'a, T>(
+        &'a self,
+        get: impl Fn(&'a ModuleExports<'r>) -> Option<(T, &'r Declaration)>,
+    ) -> Imported<'a, 'r, T> {
+        let mut private = None;
+        let mut public = Vec::new();
+        for exports in &self.imports {
+            match get(exports) {
+                Some((item, decl)) if decl.vis() == Visibility::Public => {
+                    public.push((exports.module.as_str(), item))
+                }
+                Some((_, decl)) => {
+                    private.get_or_insert((exports, decl));
+                }
+                None => {}
+            }
+        }
+
+        match (public.len(), private) {
+            (0, Some((exports, decl))) => Imported::Private(exports, decl),
+            (0, None) => Imported::NotFound,
+            (1, _) => Imported::Public(public.pop().unwrap().1),
+            _ => Imported::Ambiguous(public.into_iter().map(|(module, _)| module).collect()),
         }
     }
 
@@ -132,6 +401,8 @@ impl<'r> SemanticAnalyzer<'r> {
         let mut diags = Vec::new();
 
         diags.extend(self.resolve_names());
+        diags.extend(self.check_types());
+        diags.extend(self.check_lints());
 
         diags
     }
@@ -139,7 +410,102 @@ impl<'r> SemanticAnalyzer<'r> {
 
 #[cfg(test)]
 mod test {
+    use std::path::Path;
+
+    use rosac_parser::symbol::SymbolInner;
+
     use super::*;
+    use crate::{lint::LintLevel, module::ModuleGraph};
+
+    const UNIT_TEST_PATH: &str = "<unit test>";
+
+    /// The result of the analysis of the source files of a test.
+    struct Analysis {
+        graph: ModuleGraph,
+        /// The diagnostics of the parser and of the analysis, in the order
+        /// they were emitted.
+        diags: Vec<Diag>,
+    }
+
+    impl Analysis {
+        /// Returns the AST of the root module.
+        fn ast(&self) -> &[Declaration] {
+            &self.graph.modules[0].ast
+        }
+
+        /// Returns the messages of the errors, the lints at the warn level are
+        /// left out.
+        fn errors(&self) -> Vec<String> {
+            self.diags
+                .iter()
+                .filter(|d| d.is_error())
+                .map(|d| d.message().to_string())
+                .collect()
+        }
+
+        /// Returns the level and the message of the diagnostics.
+        fn messages(&self) -> Vec<String> {
+            self.diags
+                .iter()
+                .map(|d| format!("{}: {}", d.level().name(), d.message()))
+                .collect()
+        }
+
+        /// Returns the messages of the errors pointing in each module.
+        fn module_errors(&self) -> Vec<(String, Vec<String>)> {
+            self.graph
+                .modules
+                .iter()
+                .map(|module| {
+                    let msgs = self
+                        .diags
+                        .iter()
+                        .filter(|d| d.is_error() && module.file.contains(d.primary_span().lo))
+                        .map(|d| d.message().to_string())
+                        .collect();
+                    (module.name.clone(), msgs)
+                })
+                .collect()
+        }
+    }
+
+    /// Registers the files in the source map, in the directory of the unit
+    /// tests, then loads the first one with the modules it imports and
+    /// analyzes them with the lint levels.
+    fn analyze_files(files: &[(&str, &str)], levels: LintLevels) -> Analysis {
+        let dir = Path::new(UNIT_TEST_PATH);
+        let sm = SourceMap::new();
+        for (name, text) in files {
+            sm.add_file(dir.join(name), *text);
+        }
+
+        let dcx = DiagCtxt::new(&sm);
+        let graph = ModuleGraph::load(&dcx, dir.join(files[0].0), dir).unwrap();
+        graph.analyze(&dcx, &levels);
+        let diags = dcx.into_diags();
+        Analysis { graph, diags }
+    }
+
+    /// Analyzes the source code of a single module with the default lint
+    /// levels.
+    fn analyze_src(text: &str) -> Analysis {
+        analyze_files(&[("main.ro", text)], LintLevels::new())
+    }
+
+    /// Returns the type of the variable defined by the let statement at the
+    /// index `idx` in the first function.
+    fn let_ty(ast: &[Declaration], idx: usize) -> Option<TypeInner> {
+        let DeclarationInner::Function { block, .. } = &ast[0].decl else {
+            panic!("not a function");
+        };
+        let StatementInner::LetStmt { symbol, .. } = &block.content[idx].stmt else {
+            panic!("not a let statement");
+        };
+        let SymbolInner::Defined { ty, .. } = &*symbol.s.borrow() else {
+            panic!("the symbol is not defined");
+        };
+        ty.as_ref().map(|t| t.ty.clone())
+    }
 
     #[test]
     fn symtbl_default_lvl() {
@@ -156,10 +522,10 @@ mod test {
         let sym = Symbol::new(bob.clone());
         sym.define(
             SymbolKind::Global,
-            Type {
+            Some(Type {
                 ty: TypeInner::Int,
                 loc: Span::ZERO,
-            },
+            }),
             0,
         );
         tbl.scope_bind(bob.clone(), sym).unwrap();
@@ -179,10 +545,10 @@ mod test {
         let sym = Symbol::new(bob.clone());
         sym.define(
             SymbolKind::Global,
-            Type {
+            Some(Type {
                 ty: TypeInner::Int,
                 loc: Span::ZERO,
-            },
+            }),
             0,
         );
         tbl.scope_bind(bob.clone(), sym.clone()).unwrap();
@@ -203,8 +569,681 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn symtbl_symbol_not_found() {
         let tbl = SymbolTable::new();
         assert!(matches!(tbl.scope_lookup("Hello"), None));
     }
+
+    #[test]
+    fn if_expr_branches_agree() {
+        let diags = analyze_src(
+            "fun foo(c: bool) -> int =\n    let x: int = if c: 1 else: 2\n    let y: bool = if c:\n        true\n    else:\n        false\n    x\n",
+        )
+        .errors();
+        assert!(diags.is_empty(), "{diags:?}");
+    }
+
+    #[test]
+    fn if_expr_incompatible_branches() {
+        let diags =
+            analyze_src("fun foo(c: bool) =\n    let x: int = if c: 1 else: true\n").errors();
+        assert_eq!(
+            diags,
+            ["`if` and `else` have incompatible types, expected `int`, found `bool`"]
+        );
+    }
+
+    #[test]
+    fn typeck_valid_program() {
+        let diags = analyze_src(
+            "fun fib(n: int) -> int =\n    if n <= 1:\n        return n\n    else:\n        return fib(n - 1) + fib(n - 2)\n\nfun foo(a: uint8) -> bool =\n    let b: uint8 = 1 + a\n    b * 2 == a\n",
+        )
+        .errors();
+        assert!(diags.is_empty(), "{diags:?}");
+    }
+
+    #[test]
+    fn typeck_mismatched_let() {
+        let diags = analyze_src("fun foo() =\n    let a: bool = 1\n").errors();
+        assert_eq!(
+            diags,
+            ["mismatched types, expected `bool`, found `{integer}`"]
+        );
+    }
+
+    #[test]
+    fn diag_codes() {
+        let codes = analyze_src("fun foo() =\n    let a: bool = 1\n    bar\n")
+            .diags
+            .iter()
+            .filter(|d| d.is_error())
+            .map(|d| d.code())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, [Some(E0013), Some(E0019)]);
+    }
+
+    #[test]
+    fn similar_value_names() {
+        let src = "fun count(counter: int) -> int =\n    let total = conter\n    totl + cnt\n";
+        let suggestions = analyze_src(src)
+            .diags
+            .iter()
+            .filter(|d| d.is_error())
+            .map(|d| {
+                d.suggestions()
+                    .map(|(_, s)| s.replacement.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(suggestions, ["counter", "total", ""]);
+    }
+
+    #[test]
+    fn similar_type_and_keyword_names() {
+        let src = "type Point = object\n    x: int\n\nfun foo(p: Pont, b: bol) -> Point =\n    ture\n    Pointt(x: 1)\n";
+        let suggestions = analyze_src(src)
+            .diags
+            .iter()
+            .filter(|d| d.is_error())
+            .flat_map(|d| {
+                d.suggestions()
+                    .map(|(msg, s)| (msg.to_string(), s.replacement.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            ("a type with a similar name exists", "Point"),
+            ("a type with a similar name exists", "bool"),
+            ("a keyword with a similar name exists", "true"),
+            ("a type with a similar name exists", "Point"),
+        ];
+        assert_eq!(
+            suggestions,
+            expected.map(|(msg, name)| (msg.to_string(), name.to_string()))
+        );
+    }
+
+    const LINTED_SRC: &str = "\
+fun unused() -> int =
+    1
+
+fun main(arg: int, _ignored: int) -> int =
+    let x = 2
+    let y = 3
+    if 1 < 2:
+        let arg = arg + y
+        return arg
+    return 0
+    y
+";
+
+    #[test]
+    fn lints_default_levels() {
+        assert_eq!(
+            analyze_files(&[("main.ro", LINTED_SRC)], LintLevels::new()).messages(),
+            [
+                "error: the symbol `arg` shadows a previous definition",
+                "warning: unused variable: `x`",
+                "warning: function `unused` is never used",
+                "warning: this condition is always `true`",
+                "warning: unreachable statement",
+            ]
+        );
+    }
+
+    #[test]
+    fn unused_variable_suggestion() {
+        let analysis = analyze_files(&[("main.ro", LINTED_SRC)], LintLevels::new());
+        let file = &analysis.graph.modules[0].file;
+        let diag = analysis
+            .diags
+            .iter()
+            .find(|d| d.message() == "unused variable: `x`")
+            .unwrap();
+
+        // the warning points at the name of the variable, that is replaced.
+        let span = diag.primary_span();
+        let text =
+            &file.src[usize::from(span.lo - file.start_pos)..usize::from(span.hi - file.start_pos)];
+        assert_eq!(text, "x");
+        let suggestions = diag
+            .suggestions()
+            .map(|(msg, s)| {
+                (
+                    msg.as_ref(),
+                    s.span.clone(),
+                    s.replacement.as_str(),
+                    s.applicability,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            suggestions,
+            [(
+                "if this is intentional, prefix it with an underscore",
+                span.clone(),
+                "_x",
+                Applicability::MachineApplicable
+            )]
+        );
+    }
+
+    #[test]
+    fn lint_levels_override() {
+        let mut levels = LintLevels::new();
+        assert!(levels.set("shadowed_names", LintLevel::Allow));
+        assert!(levels.set("unused_functions", LintLevel::Allow));
+        assert!(levels.set("unreachable_code", LintLevel::Deny));
+        assert!(!levels.set("unused_variable", LintLevel::Allow));
+        assert_eq!(
+            analyze_files(&[("main.ro", LINTED_SRC)], levels).messages(),
+            [
+                "warning: unused variable: `x`",
+                "warning: this condition is always `true`",
+                "error: unreachable statement",
+            ]
+        );
+
+        // `-D warnings` denies the lints at the warn level only.
+        let mut levels = LintLevels::new();
+        assert!(levels.set("shadowed_names", LintLevel::Warn));
+        assert!(levels.set("constant_conditions", LintLevel::Allow));
+        assert!(levels.set(lint::WARNINGS, LintLevel::Deny));
+        assert_eq!(
+            analyze_files(&[("main.ro", LINTED_SRC)], levels).messages(),
+            [
+                "error: the symbol `arg` shadows a previous definition",
+                "error: unused variable: `x`",
+                "error: function `unused` is never used",
+                "error: unreachable statement",
+            ]
+        );
+    }
+
+    #[test]
+    fn error_nodes_are_skipped() {
+        let errors = analyze_src(
+            "fun a(n: int) -> int =\n    let x = )\n    n +* 2\n    x\n\nfun b( -> int =\n    1\n\nfun c() -> int =\n    a(1) + b()\n",
+        )
+        .errors();
+        // `b` failed to parse, only the errors of the parser are reported, not
+        // the call to it.
+        assert_eq!(errors.len(), 3, "{errors:?}");
+    }
+
+    #[test]
+    fn typeck_operators() {
+        let diags = analyze_src(
+            "fun foo(a: uint, b: bool, c: int8) =\n    -a\n    !a\n    b + b\n    c + 1\n    a + c\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "cannot apply unary operator `-` to type `uint`",
+                "cannot apply unary operator `!` to type `uint`",
+                "cannot apply binary operator `+` to type `bool`",
+                "mismatched types, expected `uint`, found `int8`",
+            ]
+        );
+    }
+
+    #[test]
+    fn typeck_calls() {
+        let diags = analyze_src(
+            "fun foo(a: int, b: bool) -> int =\n    foo(1)\n    foo(1, 2)\n    a(1)\n    return foo(1, true)\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "this function takes 2 arguments but 1 was supplied",
+                "mismatched types, expected `bool`, found `{integer}`",
+                "expected function, found `int`",
+            ]
+        );
+    }
+
+    #[test]
+    fn typeck_returns_and_predicates() {
+        let diags = analyze_src(
+            "fun foo(a: int) -> bool =\n    if a:\n        return\n    return a\n\nfun bar() =\n    return 1\n\nfun baz() -> int =\n    true\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "mismatched types, expected `bool`, found `int`",
+                "mismatched types, expected `bool`, found `void`",
+                "mismatched types, expected `bool`, found `int`",
+                "mismatched types, expected `void`, found `{integer}`",
+                "mismatched types, expected `int`, found `bool`",
+            ]
+        );
+    }
+
+    #[test]
+    fn infer_let_types() {
+        let analysis = analyze_src(
+            "fun foo(a: uint8, b: bool) =\n    let x = 1\n    let y = b\n    let z = 2\n    let w = z + 3\n    foo(w, y)\n",
+        );
+        let diags = analysis.errors();
+        assert!(diags.is_empty(), "{diags:?}");
+        let ast = analysis.ast();
+        assert_eq!(let_ty(ast, 0), Some(TypeInner::Int));
+        assert_eq!(let_ty(ast, 1), Some(TypeInner::Bool));
+        assert_eq!(let_ty(ast, 2), Some(TypeInner::UInt8));
+        assert_eq!(let_ty(ast, 3), Some(TypeInner::UInt8));
+    }
+
+    #[test]
+    fn infer_let_type_mismatch() {
+        let diags = analyze_src(
+            "fun foo(a: uint8, b: int8) =\n    let x = 1\n    foo(x, x)\n    let y = true\n    foo(y, 1)\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "mismatched types, expected `int8`, found `uint8`",
+                "mismatched types, expected `uint8`, found `bool`",
+            ]
+        );
+    }
+
+    #[test]
+    fn int_literal_types() {
+        let analysis = analyze_src("fun foo(a: uint16) =\n    let x = 1\n    let y = a + 2\n");
+        let diags = analysis.errors();
+        assert!(diags.is_empty(), "{diags:?}");
+
+        let DeclarationInner::Function { block, .. } = &analysis.ast()[0].decl else {
+            panic!("not a function");
+        };
+        let lit_ty = |idx: usize, f: &dyn Fn(&Expression) -> &Expression| {
+            let StatementInner::LetStmt { value, .. } = &block.content[idx].stmt else {
+                panic!("not a let statement");
+            };
+            let ExpressionInner::IntLiteral { ty, .. } = &f(value).expr else {
+                panic!("not an integer literal");
+            };
+            ty.borrow().clone()
+        };
+        assert_eq!(lit_ty(0, &|e| e), Some(TypeInner::Int));
+        assert_eq!(
+            lit_ty(1, &|e| match &e.expr {
+                ExpressionInner::BinaryExpr { rhs, .. } => rhs,
+                _ => panic!("not a binary expression"),
+            }),
+            Some(TypeInner::UInt16)
+        );
+    }
+
+    #[test]
+    fn int_literal_out_of_range() {
+        let analysis = analyze_src(
+            "fun foo(a: uint8) =\n    let x: uint8 = 300\n    let y: int8 = -128\n    let z: int8 = -129\n    let w: uint8 = -1\n    foo(256)\n    let v = 9223372036854775808\n",
+        );
+        assert_eq!(
+            analysis.errors(),
+            [
+                "cannot apply unary operator `-` to type `uint8`",
+                "literal out of range for `uint8`, `300` doesn't fit in `0..=255`",
+                "literal out of range for `int8`, `-129` doesn't fit in `-128..=127`",
+                "literal out of range for `uint8`, `256` doesn't fit in `0..=255`",
+                "literal out of range for `int`, `9223372036854775808` doesn't fit in `-9223372036854775808..=9223372036854775807`",
+            ]
+        );
+
+        // the error about a negated literal points to the negation too.
+        let file = &analysis.graph.modules[0].file;
+        let diag = analysis.diags.iter().find(|d| d.message().contains("-129"));
+        let span = diag.unwrap().primary_span();
+        let text =
+            &file.src[usize::from(span.lo - file.start_pos)..usize::from(span.hi - file.start_pos)];
+        assert_eq!(text, "-129");
+    }
+
+    #[test]
+    fn negation_of_inferred_int() {
+        let analysis = analyze_src(
+            "fun bar(a: uint8) =\n    a\n\nfun foo() =\n    let x = 1\n    let y = -x\n    bar(y)\n    let s = 2\n    let t: int8 = -s\n",
+        );
+        assert_eq!(
+            analysis.errors(),
+            ["cannot apply unary operator `-` to type `uint8`"]
+        );
+
+        let file = &analysis.graph.modules[0].file;
+        let diag = analysis.diags.iter().find(|d| d.is_error());
+        let span = diag.unwrap().primary_span();
+        let text =
+            &file.src[usize::from(span.lo - file.start_pos)..usize::from(span.hi - file.start_pos)];
+        assert_eq!(text, "-x");
+    }
+
+    #[test]
+    fn object_types() {
+        let diags = analyze_src(
+            "type Human = object\n    name: char\n    age: uint8\n\nfun older(h: Human) -> Human =\n    let age = h.age + 1\n    Human(name: h.name, age: age)\n",
+        )
+        .errors();
+        assert!(diags.is_empty(), "{diags:?}");
+    }
+
+    #[test]
+    fn object_type_errors() {
+        let diags = analyze_src(
+            "type Point = object\n    x: int,\n    y: int,\n    x: bool\n\ntype Point = object\n    z: Nope\n\ntype S = enum\n    Dot\n\ntype A = object\n    b: [B; 2]\n\ntype B = enum\n    Leaf\n    Node(A)\n\ntype C = object\n    cs: [C]\n\nfun foo(p: Point, s: S) -> bool =\n    p.z\n    s.x\n    Point(x: 1, w: 2)\n    Point(x: 1, y: 2, y: 3)\n    Shape(x: 1)\n    p.y\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "the type `Point` is defined multiple times",
+                "the field `x` is defined multiple times",
+                "cannot find type `Nope` in this scope",
+                "cannot find type `Shape` in this scope",
+                "recursive type `A` has infinite size",
+                "recursive type `B` has infinite size",
+                "no field `z` on type `Point`",
+                "no field `x` on type `S`",
+                "no field named `w` in `Point`",
+                "missing field `y` in initializer of `Point`",
+                "field `y` specified more than once",
+                "mismatched types, expected `bool`, found `int`",
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_types_and_match() {
+        let diags = analyze_src(
+            "type Shape = enum\n    Circle(int)\n    Rect(int, int)\n    Empty\n\nfun area(s: Shape) -> int =\n    match s:\n        Circle(r): r * r * 3\n        Rect(w, h): w * h\n        Empty: 0\n\nfun foo(s: Shape) -> int =\n    let x = match s:\n        Rect(_, h): h\n        _: 1\n    area(Rect(2, 3)) + area(Empty) + x\n",
+        )
+        .errors();
+        assert!(diags.is_empty(), "{diags:?}");
+    }
+
+    #[test]
+    fn match_errors() {
+        let diags = analyze_src(
+            "type Shape = enum\n    Circle(int)\n    Rect(int, int)\n    Empty\n    Point\n\nfun foo(s: Shape, a: int) =\n    match s:\n        Circle(r): r\n    match s:\n        Rect(w): true\n        Square: 1\n        _: 2\n    match a:\n        _: 0\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "non-exhaustive patterns, `Rect(_, _)`, `Empty` and `Point` not covered",
+                "this pattern has 1 field, but the variant `Rect` has 2 fields",
+                "no variant named `Square` in `Shape`",
+                "`match` arms have incompatible types, expected `bool`, found `{integer}`",
+                "`match` arms have incompatible types, expected `bool`, found `{integer}`",
+                "cannot match on type `int`, only enums can be matched",
+            ]
+        );
+    }
+
+    #[test]
+    fn fn_ptr_values_and_indirect_calls() {
+        let diags = analyze_src(
+            "fun twice(f: fun (int) -> int, x: int) -> int =\n    f(f(x))\n\nfun inc(x: int) -> int =\n    x + 1\n\nfun foo(b: bool) -> int =\n    let f = inc\n    let g: fun (int, bool) -> int = inc\n    f(true)\n    twice(inc, 2) + f(3)\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "mismatched types, expected `fun (int, bool) -> int`, found `fun (int) -> int`",
+                "mismatched types, expected `int`, found `bool`",
+            ]
+        );
+    }
+
+    #[test]
+    fn var_args_and_assignments() {
+        let diags = analyze_src(
+            "fun foo(in: int, var out: int) =\n    out = 3 * in\n\nfun bar(a: int) =\n    let mut x = 1\n    let y = 2\n    foo(a, x)\n    foo(a, y)\n    foo(a, a)\n    foo(a, 3)\n    y = 4\n    a = 5\n    x = true\n    let f: fun (int, var int) = foo\n    let g: fun (int, int) = foo\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "cannot pass `y` as a `var` argument, it is not mutable",
+                "cannot pass `a` as a `var` argument, it is not mutable",
+                "a `var` argument must be a mutable place",
+                "cannot assign to `y`, it is not mutable",
+                "cannot assign to `a`, it is not mutable",
+                "mismatched types, expected `int`, found `bool`",
+                "mismatched types, expected `fun (int, int)`, found `fun (int, var int)`",
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_and_slices() {
+        let analysis = analyze_src(
+            "fun sum(xs: [uint8]) -> uint8 =\n    xs[0] + xs[len(xs) - 1]\n\nfun foo() -> uint8 =\n    let a = [1, 2, 3]\n    let mut b: [bool; 2] = [true, false]\n    b[1] = b[0]\n    let c: [uint8; 0] = []\n    sum(a) + sum(c) + a[2]\n",
+        );
+        let diags = analysis.errors();
+        assert!(diags.is_empty(), "{diags:?}");
+
+        let DeclarationInner::Function { block, .. } = &analysis.ast()[1].decl else {
+            panic!("not a function");
+        };
+        let StatementInner::LetStmt { symbol, .. } = &block.content[0].stmt else {
+            panic!("not a let statement");
+        };
+        let SymbolInner::Defined { ty: Some(ty), .. } = &*symbol.s.borrow() else {
+            panic!("the type of the symbol isn't inferred");
+        };
+        assert_eq!(ty.ty.to_string(), "[uint8; 3]");
+    }
+
+    #[test]
+    fn array_errors() {
+        let diags = analyze_src(
+            "fun foo(a: [int; 3], i: int) =\n    let x = []\n    let y = [1, true]\n    a[3]\n    a[true]\n    i[0]\n    len(i)\n    let l = len\n    a[i] = 2\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "cannot infer the type of an empty array literal",
+                "mismatched types, expected `{integer}`, found `bool`",
+                "index out of bounds, the length is 3 but the index is 3",
+                "the type `[int; 3]` cannot be indexed by `bool`",
+                "cannot index into a value of type `int`",
+                "expected an array or a slice, found `int`",
+                "the built-in function `len` can only be called",
+                "cannot assign to this expression",
+            ]
+        );
+    }
+
+    #[test]
+    fn builtins_are_resolved_last() {
+        let diags = analyze_src(
+            "fun len(s: String) -> uint =\n    0\n\nfun foo() -> uint =\n    len(\"abc\")\n",
+        )
+        .errors();
+        assert!(diags.is_empty(), "{diags:?}");
+
+        let diags = analyze_src(
+            "fun foo(a: [int; 2]) -> uint =\n    let n = len(a)\n    let len: uint = 2\n    n + len\n",
+        )
+        .errors();
+        assert!(diags.is_empty(), "{diags:?}");
+    }
+
+    #[test]
+    fn string_type() {
+        let diags = analyze_src(
+            "type Human = object\n    firstName: String,\n    name: String\n\nfun greet(h: Human) -> String =\n    let hello = \"Hello \"\n    if h.name == \"\":\n        hello + h.firstName\n    else:\n        hello + h.name\n\nfun foo(s: String) =\n    s - s\n    s < s\n    s + 1\n    let n: int = s\n",
+        )
+        .errors();
+        assert_eq!(
+            diags,
+            [
+                "cannot apply binary operator `-` to type `String`",
+                "cannot apply binary operator `<` to type `String`",
+                "mismatched types, expected `String`, found `{integer}`",
+                "mismatched types, expected `int`, found `String`",
+            ]
+        );
+    }
+
+    #[test]
+    fn module_imports() {
+        let modules = analyze_files(
+            &[
+                (
+                    "main.ro",
+                    "import geo/point\nimport util\n\nfun main() -> int =\n    let p = origin()\n    p.x + double(p.y)\n",
+                ),
+                (
+                    "geo/point.ro",
+                    "import util\n\npub type Point = object\n    x: int\n    y: int\n\npub fun origin() -> Point =\n    Point(x: 0, y: double(0))\n",
+                ),
+                ("util.ro", "pub fun double(x: int) -> int =\n    x * 2\n"),
+            ],
+            LintLevels::new(),
+        )
+        .module_errors();
+        assert_eq!(
+            modules,
+            [
+                ("main".to_string(), vec![]),
+                ("geo/point".to_string(), vec![]),
+                ("util".to_string(), vec![]),
+            ]
+        );
+
+        let modules = analyze_files(
+            &[(
+                "main.ro",
+                "import nope\n\nfun main() -> int =\n    double(1)\n",
+            )],
+            LintLevels::new(),
+        )
+        .module_errors();
+        assert_eq!(modules.len(), 1);
+        assert!(modules[0].1[0].starts_with("unresolved import `nope`, cannot read `"));
+        assert_eq!(modules[0].1[1], "cannot found value 'double' in this scope");
+    }
+
+    #[test]
+    fn module_import_empty() {
+        let modules = analyze_files(
+            &[
+                ("main.ro", "import empty\n\nfun main() =\n    1\n"),
+                ("empty.ro", ""),
+            ],
+            LintLevels::new(),
+        )
+        .module_errors();
+        assert_eq!(
+            modules,
+            [("main".to_string(), vec![]), ("empty".to_string(), vec![])]
+        );
+    }
+
+    #[test]
+    fn module_import_cycle() {
+        let modules = analyze_files(
+            &[
+                ("main.ro", "import a\n\nfun main() =\n    foo()\n"),
+                ("a.ro", "import b\n\npub fun foo() =\n    bar()\n"),
+                ("b.ro", "import main\n\npub fun bar() =\n    main()\n"),
+            ],
+            LintLevels::new(),
+        )
+        .module_errors();
+        assert_eq!(
+            modules,
+            [
+                ("main".to_string(), vec![]),
+                ("a".to_string(), vec![]),
+                (
+                    "b".to_string(),
+                    vec![
+                        "import cycle detected: main -> a -> b -> main".to_string(),
+                        "cannot found value 'main' in this scope".to_string(),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn module_visibility() {
+        let modules = analyze_files(
+            &[
+                (
+                    "main.ro",
+                    "import shapes\n\nfun main(s: Shape, c: Color) -> int =\n    area(s) + secret()\n\nfun dot() -> Shape =\n    Dot\n",
+                ),
+                (
+                    "shapes.ro",
+                    "pub type Shape = enum\n    Dot\n    Square(int)\n\ntype Color = object\n    red: bool\n\npub fun area(s: Shape) -> int =\n    secret()\n\nfun secret() -> int =\n    42\n",
+                ),
+            ],
+            LintLevels::new(),
+        )
+        .module_errors();
+        assert_eq!(
+            modules,
+            [
+                (
+                    "main".to_string(),
+                    vec![
+                        "`Color` is private to the module `shapes`".to_string(),
+                        "`secret` is private to the module `shapes`".to_string(),
+                    ]
+                ),
+                ("shapes".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn module_named_types() {
+        let modules = analyze_files(
+            &[
+                (
+                    "main.ro",
+                    "import geo\nimport draw\n\nfun main() -> int =\n    plot(origin()) + origin().x + Secret(v: 1).v\n\nfun pos(p: Point) -> int =\n    0\n",
+                ),
+                (
+                    "geo.ro",
+                    "pub type Point = object\n    x: int\n\ntype Secret = object\n    v: int\n\npub fun origin() -> Point =\n    Point(x: 0)\n",
+                ),
+                (
+                    "draw.ro",
+                    "pub type Point = object\n    x: int\n\npub fun plot(p: Point) -> int =\n    p.x\n",
+                ),
+            ],
+            LintLevels::new(),
+        )
+        .module_errors();
+        // the fields of a type of another module are known even if its
+        // name is ambiguous.
+        assert_eq!(
+            modules,
+            [
+                (
+                    "main".to_string(),
+                    vec![
+                        "the type `Point` is ambiguous, it is defined in the modules `geo`, `draw`"
+                            .to_string(),
+                        "`Secret` is private to the module `geo`".to_string(),
+                        "mismatched types, expected `draw::Point`, found `geo::Point`".to_string(),
+                    ]
+                ),
+                ("geo".to_string(), vec![]),
+                ("draw".to_string(), vec![]),
+            ]
+        );
+    }
 }
@@ -0,0 +1,352 @@
+//! Module responsible for the lints, the checks of code that is valid but is
+//! likely a mistake, like a variable that is never used.
+//!
+//! # Lint levels
+//!
+//! Each lint has a level: `allow` doesn't report it, `warn` reports it as a
+//! warning and `deny` as an error. A lint has its default level unless its
+//! level is set on the command line with `-A`, `-W` or `-D` followed by the
+//! name of the lint, and `-D warnings` denies every lint at the warn level.
+//!
+//! The unused variables and functions and the shadowed names are found by
+//! the name resolution, the other lints are checked by walking the AST after
+//! the type checking.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rosa_errors::DiagMessage;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    /// The command line flag setting a lint to this level.
+    pub fn flag(&self) -> &'static str {
+        match self {
+            LintLevel::Allow => "-A",
+            LintLevel::Warn => "-W",
+            LintLevel::Deny => "-D",
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Lint {
+    /// Name of the lint, used to set its level on the command line.
+    pub name: &'static str,
+    pub default_level: LintLevel,
+    pub desc: &'static str,
+}
+
+pub static UNUSED_VARIABLES: Lint = Lint {
+    name: "unused_variables",
+    default_level: LintLevel::Warn,
+    desc: "detects variables and arguments that are never used",
+};
+
+pub static UNUSED_FUNCTIONS: Lint = Lint {
+    name: "unused_functions",
+    default_level: LintLevel::Warn,
+    desc: "detects private functions that are never used",
+};
+
+pub static UNREACHABLE_CODE: Lint = Lint {
+    name: "unreachable_code",
+    default_level: LintLevel::Warn,
+    desc: "detects statements after a return statement",
+};
+
+/// Unlike the other lints it denies by default: shadowing a symbol used to be
+/// an error of the name resolution, the lint only allows to opt out of it.
+pub static SHADOWED_NAMES: Lint = Lint {
+    name: "shadowed_names",
+    default_level: LintLevel::Deny,
+    desc: "detects variables and arguments shadowing a symbol of an enclosing scope",
+};
+
+pub static CONSTANT_CONDITIONS: Lint = Lint {
+    name: "constant_conditions",
+    default_level: LintLevel::Warn,
+    desc: "detects if conditions that are always true or always false",
+};
+
+/// All the lints.
+pub static LINTS: &[&Lint] = &[
+    &UNUSED_VARIABLES,
+    &UNUSED_FUNCTIONS,
+    &UNREACHABLE_CODE,
+    &SHADOWED_NAMES,
+    &CONSTANT_CONDITIONS,
+];
+
+/// Name of the group of the lints at the warn level, only usable on the
+/// command line.
+pub const WARNINGS: &str = "warnings";
+
+/// The levels of the lints set on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    /// Levels by lint name.
+    levels: HashMap<&'static str, LintLevel>,
+    /// Level of the lints at the warn level, set with the `warnings` group.
+    warnings: Option<LintLevel>,
+}
+
+/// Where the level of a lint comes from.
+enum LevelSource {
+    Default,
+    CommandLine,
+    Warnings(LintLevel),
+}
+
+impl LintLevels {
+    pub fn new() -> LintLevels {
+        LintLevels::default()
+    }
+
+    /// Sets the level of the lint `name`, or of the `warnings` group. Returns
+    /// false if there is no lint with this name.
+    pub fn set(&mut self, name: &str, level: LintLevel) -> bool {
+        if name == WARNINGS {
+            self.warnings = Some(level);
+            return true;
+        }
+        match LINTS.iter().find(|lint| lint.name == name) {
+            Some(lint) => {
+                self.levels.insert(lint.name, level);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the level of the lint.
+    pub fn level(&self, lint: &Lint) -> LintLevel {
+        self.level_and_source(lint).0
+    }
+
+    fn level_and_source(&self, lint: &Lint) -> (LintLevel, LevelSource) {
+        let (level, source) = match self.levels.get(lint.name) {
+            Some(&level) => (level, LevelSource::CommandLine),
+            None => (lint.default_level, LevelSource::Default),
+        };
+        // like a warning, the group applies to the lints set to warn too.
+        match self.warnings {
+            Some(group) if level == LintLevel::Warn => (group, LevelSource::Warnings(group)),
+            _ => (level, source),
+        }
+    }
+}
+
+impl<'r> SemanticAnalyzer<'r> {
+    /// Sets the levels of the lints, it must be called before the analysis.
+    pub fn set_lint_levels(&mut self, levels: LintLevels) {
+        self.lint_levels = levels;
+    }
+
+    /// Returns the diagnostic of the lint at its level, or `None` if the lint
+    /// is allowed. The note tells where the level comes from.
+    pub fn lint(&self, lint: &Lint, msg: impl Into<DiagMessage>, loc: Span) -> Option<Diag> {
+        let (level, source) = self.lint_levels.level_and_source(lint);
+        let diag = match level {
+            LintLevel::Allow => return None,
+            LintLevel::Warn => self.dcx.struct_warn(msg, loc),
+            LintLevel::Deny => self.dcx.struct_err(msg, loc),
+        };
+
+        let note = match source {
+            LevelSource::Default => format!("`{}` on by default", lint.name),
+            LevelSource::CommandLine => format!(
+                "requested on the command line with `{} {}`",
+                level.flag(),
+                lint.name
+            ),
+            LevelSource::Warnings(group) => format!(
+                "`{} {}` implied by `{} {WARNINGS}`",
+                level.flag(),
+                lint.name,
+                group.flag()
+            ),
+        };
+        Some(diag.with_note(note))
+    }
+
+    /// Checks the lints walking the AST, it must be called after the type
+    /// checking.
+    #[must_use]
+    pub fn check_lints(&self) -> Vec<Diag> {
+        let mut diags = Vec::new();
+
+        for decl in self.ast.iter() {
+            if let DeclarationInner::Function { block, .. } = &decl.decl {
+                self.lint_block(block, &mut diags);
+            }
+        }
+
+        diags
+    }
+
+    fn lint_block(&self, block: &Block<Statement>, diags: &mut Vec<Diag>) {
+        for stmt in &block.content {
+            match &stmt.stmt {
+                StatementInner::LetStmt { value, .. } => self.lint_expr(value, diags),
+                StatementInner::ExprStmt(expr) | StatementInner::ReturnStmt(Some(expr)) => {
+                    self.lint_expr(expr, diags)
+                }
+                StatementInner::AssignStmt { place, value } => {
+                    self.lint_expr(place, diags);
+                    self.lint_expr(value, diags);
+                }
+                StatementInner::ReturnStmt(None) | StatementInner::Error => {}
+            }
+        }
+
+        // the statements after the first return are reported together.
+        let ret = block
+            .content
+            .iter()
+            .position(|stmt| matches!(stmt.stmt, StatementInner::ReturnStmt(_)));
+        if let Some(i) = ret.filter(|&i| i + 1 < block.content.len()) {
+            let first = &block.content[i + 1];
+            let last = block.content.last().unwrap();
+            let loc = Span::from_inner(first.loc.lo, last.loc.hi);
+            diags.extend(
+                self.lint(&UNREACHABLE_CODE, "unreachable statement", loc)
+                    .map(|diag| {
+                        diag.with_label("unreachable statement").with_span_label(
+                            block.content[i].loc.clone(),
+                            "any code following this return statement is unreachable",
+                        )
+                    }),
+            );
+        }
+    }
+
+    fn lint_expr(&self, expr: &Expression, diags: &mut Vec<Diag>) {
+        match &expr.expr {
+            ExpressionInner::BinaryExpr { lhs, rhs, .. } => {
+                self.lint_expr(lhs, diags);
+                self.lint_expr(rhs, diags);
+            }
+            ExpressionInner::UnaryExpr { operand, .. } => self.lint_expr(operand, diags),
+            ExpressionInner::CallExpr { callee, args } => {
+                self.lint_expr(callee, diags);
+                for arg in args {
+                    self.lint_expr(arg, diags);
+                }
+            }
+            ExpressionInner::FieldExpr { expr, .. } => self.lint_expr(expr, diags),
+            ExpressionInner::ObjectExpr { fields, .. } => {
+                for (_, value) in fields {
+                    self.lint_expr(value, diags);
+                }
+            }
+            ExpressionInner::IfExpr {
+                predicate,
+                body,
+                else_branch,
+            } => {
+                self.lint_expr(predicate, diags);
+                if let Some(value) = const_bool(predicate) {
+                    diags.extend(
+                        self.lint(
+                            &CONSTANT_CONDITIONS,
+                            format!("this condition is always `{value}`"),
+                            predicate.loc.clone(),
+                        )
+                        .map(|diag| {
+                            let branch = if value { "else branch" } else { "body" };
+                            diag.with_help(format!("the {branch} of the if is never executed"))
+                        }),
+                    );
+                }
+                self.lint_block(body, diags);
+                if let Some(other) = else_branch {
+                    self.lint_block(other, diags);
+                }
+            }
+            ExpressionInner::MatchExpr { scrutinee, arms } => {
+                self.lint_expr(scrutinee, diags);
+                for arm in arms {
+                    self.lint_block(&arm.body, diags);
+                }
+            }
+            ExpressionInner::IndexExpr { expr, index } => {
+                self.lint_expr(expr, diags);
+                self.lint_expr(index, diags);
+            }
+            ExpressionInner::ArrayLiteral(elems) => {
+                for elem in elems {
+                    self.lint_expr(elem, diags);
+                }
+            }
+            ExpressionInner::IntLiteral { .. }
+            | ExpressionInner::BoolLiteral(_)
+            | ExpressionInner::CharLiteral(_)
+            | ExpressionInner::StrLiteral(_)
+            | ExpressionInner::SymbolExpr(_)
+            | ExpressionInner::Error => {}
+        }
+    }
+}
+
+/// Returns the value of a condition made only of literals, e.g: `!false` or
+/// `1 < 2`.
+fn const_bool(expr: &Expression) -> Option<bool> {
+    match &expr.expr {
+        ExpressionInner::BoolLiteral(b) => Some(*b),
+        ExpressionInner::UnaryExpr {
+            op: UnaryOp::Not,
+            operand,
+        } => const_bool(operand).map(|b| !b),
+        ExpressionInner::BinaryExpr { lhs, op, rhs } => {
+            if let (Some(lhs), Some(rhs)) = (const_int(lhs), const_int(rhs)) {
+                return match op {
+                    BinaryOp::CompLT => Some(lhs < rhs),
+                    BinaryOp::CompGT => Some(lhs > rhs),
+                    BinaryOp::CompLTE => Some(lhs <= rhs),
+                    BinaryOp::CompGTE => Some(lhs >= rhs),
+                    BinaryOp::CompEq => Some(lhs == rhs),
+                    BinaryOp::CompNe => Some(lhs != rhs),
+                    _ => None,
+                };
+            }
+            let (lhs, rhs) = (const_bool(lhs)?, const_bool(rhs)?);
+            match op {
+                BinaryOp::CompEq => Some(lhs == rhs),
+                BinaryOp::CompNe => Some(lhs != rhs),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the value of an integer literal, maybe negated.
+fn const_int(expr: &Expression) -> Option<i128> {
+    match &expr.expr {
+        ExpressionInner::IntLiteral { value, .. } => Some(*value as i128),
+        ExpressionInner::UnaryExpr {
+            op: UnaryOp::Negation,
+            operand,
+        } => const_int(operand).map(|i| -i),
+        _ => None,
+    }
+}
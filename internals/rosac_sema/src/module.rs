@@ -0,0 +1,209 @@
+//! Module responsible for loading the modules of a program, resolving their
+//! imports to files and analyzing them.
+//!
+//! # Module resolution
+//!
+//! An import like `import std/io` is resolved to the file `std/io.ro`
+//! relative to the search path. The modules are loaded with a depth-first
+//! walk of the imports, an import of a module still being loaded is an import
+//! cycle and is reported with the chain of imports.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use rosac_lexer::{abs::BufferedLexer, Lexer};
+use rosac_parser::Parser;
+
+use crate::{lint::LintLevels, prelude::*, ModuleExports};
+
+/// Extension of the Rosa source files.
+pub const SOURCE_EXT: &str = "ro";
+
+#[derive(Debug)]
+pub struct Module {
+    /// Name of the module, the file stem for the root module and the import
+    /// path for the others, e.g: `std/io`
+    pub name: String,
+    /// The source file of the module, registered in the source map.
+    pub file: Rc<SourceFile>,
+    pub ast: Vec<Declaration>,
+    /// Indices of the modules imported by this module.
+    pub imports: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct ModuleGraph {
+    /// The modules loaded, the root module is the first one.
+    pub modules: Vec<Module>,
+    /// Indices of the modules, a module always comes after the modules it
+    /// imports.
+    pub order: Vec<usize>,
+}
+
+impl ModuleGraph {
+    /// Loads the root module and, recursively, the modules it imports from
+    /// the search path, their files are registered in the source map of the
+    /// diagnostic context. A file already registered in the source map is not
+    /// read again. Only the failure to read the root file is an error, the
+    /// other failures are emitted as diagnostics.
+    pub fn load(
+        dcx: &DiagCtxt<'_>,
+        root: impl AsRef<Path>,
+        search_path: impl AsRef<Path>,
+    ) -> io::Result<ModuleGraph> {
+        let root = root.as_ref();
+        let file = read_file(dcx, root)?;
+        let name = root
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut loader = Loader {
+            dcx,
+            search_path: search_path.as_ref().to_path_buf(),
+            modules: Vec::new(),
+            by_path: HashMap::new(),
+            stack: Vec::new(),
+            order: Vec::new(),
+        };
+        loader.load_module(name, file);
+
+        Ok(ModuleGraph {
+            modules: loader.modules,
+            order: loader.order,
+        })
+    }
+
+    /// Analyzes the modules, the imported modules first, and emits the
+    /// diagnostics, the lints are reported at the given levels.
+    pub fn analyze<'r>(&'r self, dcx: &'r DiagCtxt<'r>, lint_levels: &LintLevels) {
+        let mut exports: Vec<Option<ModuleExports<'r>>> = vec![None; self.modules.len()];
+
+        for &id in &self.order {
+            let module = &self.modules[id];
+            let mut seman = SemanticAnalyzer::new(&module.ast, dcx);
+            seman.set_module_id(id);
+            seman.set_lint_levels(lint_levels.clone());
+
+            for &dep in &module.imports {
+                seman.import(
+                    exports[dep]
+                        .clone()
+                        .expect("the imported modules are analyzed first"),
+                );
+            }
+
+            dcx.emit_diags(seman.analyze());
+            exports[id] = Some(seman.exports(&module.name));
+        }
+    }
+}
+
+struct Loader<'a, 'r> {
+    dcx: &'a DiagCtxt<'r>,
+    search_path: PathBuf,
+    modules: Vec<Module>,
+    /// Index of the modules by the canonical path of their file.
+    by_path: HashMap<PathBuf, usize>,
+    /// The modules being loaded, the last one is the importer.
+    stack: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl Loader<'_, '_> {
+    fn load_module(&mut self, name: String, file: Rc<SourceFile>) -> usize {
+        let path = file.path.clone();
+        let ast = Parser::new(BufferedLexer::new(Lexer::new(&file, self.dcx))).begin_parsing();
+
+        let id = self.modules.len();
+        self.by_path.insert(canonical(&path), id);
+        self.stack.push(id);
+
+        let import_decls = ast
+            .iter()
+            .filter_map(|decl| match &decl.decl {
+                DeclarationInner::Import { path } => Some((path.join("/"), decl.loc.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        self.modules.push(Module {
+            name,
+            file,
+            ast,
+            imports: Vec::new(),
+        });
+
+        let mut imports = Vec::new();
+        for (import, loc) in import_decls {
+            let file = self.search_path.join(&import).with_extension(SOURCE_EXT);
+
+            let dep = match self.by_path.get(&canonical(&file)) {
+                Some(&dep) if self.stack.contains(&dep) => {
+                    let pos = self.stack.iter().position(|&m| m == dep).unwrap();
+                    let chain = self.stack[pos..]
+                        .iter()
+                        .map(|&m| self.modules[m].name.as_str())
+                        .chain([import.as_str()])
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    self.dcx.emit_diag(
+                        self.dcx
+                            .struct_err(format!("import cycle detected: {chain}"), loc)
+                            .with_code(E0012),
+                    );
+                    continue;
+                }
+                Some(&dep) => dep,
+                None => match read_file(self.dcx, &file) {
+                    Ok(file) => self.load_module(import, file),
+                    Err(err) => {
+                        self.dcx.emit_diag(
+                            self.dcx
+                                .struct_err(
+                                    format!(
+                                        "unresolved import `{import}`, cannot read `{}`: {err}",
+                                        file.display()
+                                    ),
+                                    loc,
+                                )
+                                .with_code(E0011),
+                        );
+                        continue;
+                    }
+                },
+            };
+
+            if !imports.contains(&dep) {
+                imports.push(dep);
+            }
+        }
+
+        self.modules[id].imports = imports;
+
+        self.stack.pop();
+        self.order.push(id);
+        id
+    }
+}
+
+/// Returns the source file registered in the source map with this path, or
+/// reads and registers it. The path is kept as it is written in the source
+/// map, it's the one shown in the diagnostics.
+fn read_file(dcx: &DiagCtxt<'_>, path: &Path) -> io::Result<Rc<SourceFile>> {
+    let sm = dcx.source_map();
+    match sm.file_by_path(path) {
+        Some(file) => Ok(file),
+        None => Ok(sm.add_file(path, fs::read_to_string(path)?)),
+    }
+}
+
+/// Returns the canonical path of the file if it exists, so that a module
+/// imported with different paths is loaded only once.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -3,12 +3,20 @@
 //!
 //! # Name Resolution
 //!
-//! The name resolution is divided in two phase:
-//! 1. Walkthrough the 'Declaration's and bind the decl's name to their symbol
-//! 2. Then the rest of the AST, with the scopes, normal
+//! The name resolution is divided in three phase:
+//! 1. Walkthrough the type 'Declaration's and bind their name to the type
+//!    they declare, so the types used in the declarations can be resolved
+//! 2. Walkthrough the 'Declaration's and bind the decl's name to their symbol
+//! 3. Then the rest of the AST, with the scopes, normal
 
+use std::cell::RefCell;
+
+use rosa_comm::edit_distance::find_best_match;
+use rosac_lexer::tokens::Keyword;
 use rosac_parser::symbol::SymbolInner;
 
+use crate::builtin::BUILTINS;
+use crate::lint::{SHADOWED_NAMES, UNUSED_FUNCTIONS, UNUSED_VARIABLES};
 use crate::prelude::*;
 
 impl<'r> SemanticAnalyzer<'r> {
@@ -16,7 +24,14 @@ impl<'r> SemanticAnalyzer<'r> {
     pub fn resolve_names(&mut self) -> Vec<Diag> {
         let mut diags = Vec::new();
 
-        // 1st we declare every symbol
+        // 1st we declare every type
+        for decl in self.ast.iter() {
+            if let DeclarationInner::Object { .. } | DeclarationInner::Enum { .. } = decl.decl {
+                diags.extend(self.declare_type(decl));
+            }
+        }
+
+        // 2nd we declare every symbol
         for decl in self.ast.iter() {
             diags.extend(self.resolve_decl(decl));
         }
@@ -26,22 +41,37 @@ impl<'r> SemanticAnalyzer<'r> {
             diags.extend(self.visit_decl(decl));
         }
 
+        diags.extend(self.report_unused_functions());
+
         diags
     }
 
     #[must_use]
-    pub fn resolve_decl(&mut self, decl: &Declaration) -> Vec<Diag> {
+    pub fn resolve_decl(&mut self, decl: &'r Declaration) -> Vec<Diag> {
         let mut diags = Vec::new();
 
-        if decl.vis != Visibility::Private {
-            diags.push(self.dcx.struct_warn(
-                "visibility other than Private is not supported, (treated as private)",
-                decl.loc.clone(),
-            ));
-        }
-
         let res = match decl.decl {
             DeclarationInner::Function { .. } => self.resolve_fun_decl(decl),
+            DeclarationInner::Object { .. } | DeclarationInner::Enum { .. } => {
+                self.resolve_type_decl(decl)
+            }
+            // the imports are resolved when loading the modules.
+            DeclarationInner::Import { .. } => Vec::new(),
+            // the error was reported by the parser, the name of the function
+            // is bound without type so its uses are not reported.
+            DeclarationInner::Error { ref name } => {
+                self.has_error_nodes = true;
+                if let Some(name) = name {
+                    // a redefinition isn't reported, the declaration may not
+                    // be what was meant.
+                    let _ = self.table.scope_bind_at(
+                        name.clone(),
+                        Symbol::new_def(name.clone(), SymbolKind::Global, None, self.decl_counter),
+                        decl.loc.clone(),
+                    );
+                }
+                Vec::new()
+            }
         };
         diags.extend(res);
 
@@ -54,37 +84,176 @@ impl<'r> SemanticAnalyzer<'r> {
     pub fn resolve_fun_decl(&mut self, decl: &Declaration) -> Vec<Diag> {
         let (name, args, ret, loc) = match &decl.decl {
             DeclarationInner::Function {
-                name, args, ret, ..
-            } => (name, args, ret, decl.loc.clone()),
-            // _ => panic!(
-            //     "resolving names for functions declarations but it's not a function declaration"
-            // ),
+                name,
+                name_loc,
+                args,
+                ret,
+                ..
+            } => (name, args, ret, name_loc.clone()),
+            _ => panic!(
+                "resolving names for functions declarations but it's not a function declaration"
+            ),
         };
 
         let mut diags = Vec::new();
+        // the types are resolved before being copied in the type of the
+        // symbol.
+        for arg in args {
+            diags.extend(self.resolve_ty(&arg.ty));
+        }
+        if let Some(ret) = ret {
+            diags.extend(self.resolve_ty(ret));
+        }
 
-        let res = self.table.scope_bind(
+        let res = self.table.scope_bind_at(
             name.clone(),
             Symbol::new_def(
                 name.clone(),
                 SymbolKind::Global,
-                Type {
+                Some(Type {
                     ty: TypeInner::FnPtr {
-                        args: args.iter().map(|a| a.1.clone()).collect(),
+                        args: args
+                            .iter()
+                            .map(|a| FnPtrArg {
+                                ty: a.ty.clone(),
+                                var: a.var,
+                            })
+                            .collect(),
                         ret: ret.clone().map(Box::new),
                     },
                     loc: Span::ZERO,
-                },
+                }),
                 self.decl_counter,
             ),
+            loc.clone(),
         );
         match res {
             Ok(()) => {}
-            Err(SymTabError::ShadowSymbol) => diags.push(self.dcx.struct_err(
-                format!("the symbol '{name}' is defined multiple times"),
-                loc, // TODO: here it would be cool to just point to the
-                     //prototype of the function
-            )),
+            Err(SymTabError::ShadowSymbol) => diags.push(self.redefined(name, loc)),
+            Err(_) => unreachable!(),
+        }
+
+        diags
+    }
+
+    /// Binds the name of the type declaration to the type it declares.
+    #[must_use]
+    pub fn declare_type(&mut self, decl: &'r Declaration) -> Vec<Diag> {
+        let name = match &decl.decl {
+            DeclarationInner::Object { name, .. } | DeclarationInner::Enum { name, .. } => name,
+            _ => panic!("declaring a type but it's not a type declaration"),
+        };
+
+        let mut diags = Vec::new();
+
+        if let Some(prev) = self.types.get(name) {
+            diags.push(
+                self.dcx
+                    .struct_err(
+                        format!("the type `{name}` is defined multiple times"),
+                        decl.name_loc(),
+                    )
+                    .with_code(E0015)
+                    .with_label(format!("`{name}` redefined here"))
+                    .with_span_label(
+                        prev.name_loc(),
+                        format!("previous definition of `{name}` here"),
+                    ),
+            );
+        } else {
+            self.types.insert(name.clone(), decl);
+        }
+
+        diags
+    }
+
+    #[must_use]
+    pub fn resolve_type_decl(&mut self, decl: &'r Declaration) -> Vec<Diag> {
+        let name = match &decl.decl {
+            DeclarationInner::Object { name, .. } | DeclarationInner::Enum { name, .. } => name,
+            _ => panic!("resolving names for type declarations but it's not a type declaration"),
+        };
+
+        let mut diags = Vec::new();
+
+        match &decl.decl {
+            DeclarationInner::Object { fields, .. } => {
+                for (i, field) in fields.iter().enumerate() {
+                    diags.extend(self.resolve_ty(&field.ty));
+                    if let Some(prev) = fields[..i].iter().find(|f| f.name == field.name) {
+                        diags.push(
+                            self.dcx
+                                .struct_err(
+                                    format!("the field `{}` is defined multiple times", field.name),
+                                    field.loc.clone(),
+                                )
+                                .with_code(E0016)
+                                .with_label(format!("`{}` redefined here", field.name))
+                                .with_span_label(
+                                    prev.loc.clone(),
+                                    format!("previous definition of `{}` here", field.name),
+                                ),
+                        );
+                    }
+                }
+            }
+            DeclarationInner::Enum { variants, .. } => {
+                for (i, variant) in variants.iter().enumerate() {
+                    for ty in &variant.payload {
+                        diags.extend(self.resolve_ty(ty));
+                    }
+                    diags.extend(self.resolve_variant(name, variant, i as u32));
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        diags
+    }
+
+    /// Binds the variant of the enum `name` to a symbol, the variants without
+    /// payload are values of the enum and the others are functions returning
+    /// the enum.
+    #[must_use]
+    pub fn resolve_variant(&mut self, name: &str, variant: &Variant, which: u32) -> Vec<Diag> {
+        let mut diags = Vec::new();
+
+        let enum_ty = Type {
+            ty: TypeInner::Named {
+                name: name.to_string(),
+                module: RefCell::new(Some(self.module)),
+            },
+            loc: Span::ZERO,
+        };
+        let ty = if variant.payload.is_empty() {
+            enum_ty
+        } else {
+            Type {
+                ty: TypeInner::FnPtr {
+                    args: variant
+                        .payload
+                        .iter()
+                        .map(|ty| FnPtrArg {
+                            ty: ty.clone(),
+                            var: false,
+                        })
+                        .collect(),
+                    ret: Some(Box::new(enum_ty)),
+                },
+                loc: Span::ZERO,
+            }
+        };
+
+        let res = self.table.scope_bind_at(
+            variant.name.clone(),
+            Symbol::new_def(variant.name.clone(), SymbolKind::Variant, Some(ty), which),
+            variant.loc.clone(),
+        );
+        match res {
+            Ok(()) => {}
+            Err(SymTabError::ShadowSymbol) => {
+                diags.push(self.redefined(&variant.name, variant.loc.clone()))
+            }
             Err(_) => unreachable!(),
         }
 
@@ -95,8 +264,14 @@ impl<'r> SemanticAnalyzer<'r> {
     pub fn visit_decl(&mut self, decl: &Declaration) -> Vec<Diag> {
         let mut diags = Vec::new();
 
-        let res = match decl.decl {
+        let res = match &decl.decl {
             DeclarationInner::Function { .. } => self.visit_fun_decl(decl),
+            // the types of the type declarations were resolved with their
+            // name.
+            DeclarationInner::Object { .. }
+            | DeclarationInner::Enum { .. }
+            | DeclarationInner::Import { .. }
+            | DeclarationInner::Error { .. } => Vec::new(),
         };
         diags.extend(res);
 
@@ -105,33 +280,35 @@ impl<'r> SemanticAnalyzer<'r> {
 
     #[must_use]
     pub fn visit_fun_decl(&mut self, decl: &Declaration) -> Vec<Diag> {
-        let (args, block, loc) = match &decl.decl {
-            DeclarationInner::Function { args, block, .. } => (args, block, decl.loc.clone()),
-            // _ => panic!(
-            //     "resolving names for functions declarations but it's not a function declaration"
-            // ),
+        let (args, block) = match &decl.decl {
+            DeclarationInner::Function { args, block, .. } => (args, block),
+            _ => panic!(
+                "resolving names for functions declarations but it's not a function declaration"
+            ),
         };
         let mut diags = Vec::new();
+
         self.table.scope_enter();
+        self.local_counter = 0;
+        self.fun_has_error_nodes = false;
 
-        for (i, (name, ty)) in args.iter().enumerate() {
+        for (i, arg) in args.iter().enumerate() {
             let i = i as u32;
-            let res = self.table.scope_bind(
+            let name = &arg.name;
+            let kind = if arg.var {
+                SymbolKind::VarArg
+            } else {
+                SymbolKind::Arg
+            };
+            diags.extend(self.bind_local(
                 name.clone(),
-                Symbol::new_def(name.clone(), SymbolKind::Arg, ty.clone(), i),
-            );
-            match res {
-                Ok(()) => {}
-                Err(SymTabError::ShadowSymbol) => diags.push(self.dcx.struct_err(
-                    format!("the symbol '{name}' is defined multiple times"),
-                    loc.clone(), // TODO: here it would be cool to just point to the
-                                 //prototype of the function
-                )),
-                Err(_) => unreachable!(),
-            }
+                Symbol::new_def(name.clone(), kind, Some(arg.ty.clone()), i),
+                arg.name_loc.clone(),
+            ));
         }
 
         diags.extend(self.visit_stmt_block(block));
+        diags.extend(self.report_unused_locals());
 
         // here we unwrap because it would be a terrible error to let the compiler continue
         // after trying to exit the global scope in this context
@@ -147,6 +324,7 @@ impl<'r> SemanticAnalyzer<'r> {
         for stmt in &block.content {
             diags.extend(self.visit_stmt(stmt));
         }
+        diags.extend(self.report_unused_locals());
         // here we unwrap because it would be a terrible error to let the compiler continue
         // after trying to exit the global scope in this context
         self.table.scope_exit().unwrap();
@@ -158,21 +336,40 @@ impl<'r> SemanticAnalyzer<'r> {
     pub fn visit_stmt(&mut self, stmt: &Statement) -> Vec<Diag> {
         let mut diags = Vec::new();
         match &stmt.stmt {
-            StatementInner::IfStmt {
-                predicate,
-                body,
-                else_branch,
+            StatementInner::LetStmt {
+                symbol,
+                name_loc,
+                mutable,
+                ty,
+                value,
             } => {
-                diags.extend(self.visit_expr(predicate));
-                diags.extend(self.visit_stmt_block(body));
-                if let Some(other) = else_branch {
-                    diags.extend(self.visit_stmt_block(other));
+                // the value is visited before binding the name, so the
+                // variable can't be used in its own definition.
+                diags.extend(self.visit_expr(value));
+                if let Some(ty) = ty {
+                    diags.extend(self.resolve_ty(ty));
                 }
+
+                let name = symbol.name();
+                let kind = if *mutable {
+                    SymbolKind::MutLocal
+                } else {
+                    SymbolKind::Local
+                };
+                symbol.define(kind, ty.clone(), self.local_counter);
+                self.local_counter += 1;
+
+                diags.extend(self.bind_local(name, symbol.clone(), name_loc.clone()));
             }
             StatementInner::ExprStmt(expr) | StatementInner::ReturnStmt(Some(expr)) => {
                 diags.extend(self.visit_expr(expr));
             }
+            StatementInner::AssignStmt { place, value } => {
+                diags.extend(self.visit_expr(place));
+                diags.extend(self.visit_expr(value));
+            }
             StatementInner::ReturnStmt(None) => {}
+            StatementInner::Error => self.error_node(),
         }
         diags
     }
@@ -189,11 +386,46 @@ impl<'r> SemanticAnalyzer<'r> {
                 };
                 if let Some(found) = self.table.scope_lookup(&name) {
                     *symbol.s.borrow_mut() = found.s.borrow().clone();
-                } else {
-                    diags.push(self.dcx.struct_err(
-                        format!("cannot found value '{}' in this scope", name),
-                        expr.loc.clone(),
-                    ))
+                    self.table.mark_used(&name);
+                    break 'out;
+                }
+
+                match self.lookup_import(&name) {
+                    Imported::Public(found) => *symbol.s.borrow_mut() = found.s.borrow().clone(),
+                    Imported::NotFound if self.define_builtin(symbol) => {}
+                    Imported::NotFound => {
+                        let mut diag = self
+                            .dcx
+                            .struct_err(
+                                format!("cannot found value '{}' in this scope", name),
+                                expr.loc.clone(),
+                            )
+                            .with_code(E0013);
+                        if let Some(similar) = self.similar_value(&name) {
+                            diag = diag.with_suggestion(
+                                expr.loc.clone(),
+                                "a value with a similar name exists",
+                                similar,
+                                Applicability::MaybeIncorrect,
+                            );
+                        } else if let Some(kw) =
+                            find_best_match(&name, Keyword::ALL.iter().map(Keyword::as_str))
+                        {
+                            diag = diag.with_suggestion(
+                                expr.loc.clone(),
+                                "a keyword with a similar name exists",
+                                kw,
+                                Applicability::MaybeIncorrect,
+                            );
+                        }
+                        diags.push(diag)
+                    }
+                    Imported::Private(exports, decl) => {
+                        diags.push(self.private_item(&name, exports, decl, expr.loc.clone()))
+                    }
+                    Imported::Ambiguous(modules) => {
+                        diags.push(self.ambiguous_item("value", &name, &modules, expr.loc.clone()))
+                    }
                 }
             }
             ExpressionInner::BinaryExpr { lhs, rhs, .. } => {
@@ -203,13 +435,344 @@ impl<'r> SemanticAnalyzer<'r> {
             ExpressionInner::UnaryExpr { operand, .. } => {
                 diags.extend(self.visit_expr(operand));
             }
+            ExpressionInner::CallExpr { callee, args } => {
+                diags.extend(self.visit_expr(callee));
+                for arg in args {
+                    diags.extend(self.visit_expr(arg));
+                }
+            }
+            ExpressionInner::MatchExpr { scrutinee, arms } => {
+                diags.extend(self.visit_expr(scrutinee));
+                for arm in arms {
+                    self.table.scope_enter();
+                    if let PatternInner::Variant { bindings, .. } = &arm.pattern.pat {
+                        for (binding, loc) in bindings {
+                            diags.extend(self.bind_pattern_var(binding, loc));
+                        }
+                    }
+                    diags.extend(self.visit_stmt_block(&arm.body));
+                    diags.extend(self.report_unused_locals());
+                    // here we unwrap because it would be a terrible error to let the compiler continue
+                    // after trying to exit the global scope in this context
+                    self.table.scope_exit().unwrap();
+                }
+            }
+            ExpressionInner::IndexExpr { expr, index } => {
+                diags.extend(self.visit_expr(expr));
+                diags.extend(self.visit_expr(index));
+            }
+            ExpressionInner::ArrayLiteral(elems) => {
+                for elem in elems {
+                    diags.extend(self.visit_expr(elem));
+                }
+            }
+            ExpressionInner::FieldExpr { expr, .. } => {
+                diags.extend(self.visit_expr(expr));
+            }
+            ExpressionInner::ObjectExpr { name, fields } => {
+                // the object expression starts with the name of the type.
+                let name_loc = Span::from_inner(expr.loc.lo, expr.loc.lo + name.len().into());
+                if let Err(diag) = self.resolve_type_name(name, name_loc) {
+                    diags.push(diag);
+                }
+                for (_, value) in fields {
+                    diags.extend(self.visit_expr(value));
+                }
+            }
+            ExpressionInner::IfExpr {
+                predicate,
+                body,
+                else_branch,
+            } => {
+                diags.extend(self.visit_expr(predicate));
+                diags.extend(self.visit_stmt_block(body));
+                if let Some(other) = else_branch {
+                    diags.extend(self.visit_stmt_block(other));
+                }
+            }
             // we don't use the wildcard `_` pattern because it forces us to
             // adjust this code when a new expression is created
-            ExpressionInner::IntLiteral(_)
+            ExpressionInner::IntLiteral { .. }
             | ExpressionInner::BoolLiteral(_)
             | ExpressionInner::CharLiteral(_)
             | ExpressionInner::StrLiteral(_) => {}
+            ExpressionInner::Error => self.error_node(),
         }
         diags
     }
+
+    /// Defines the variable bound by a pattern, its type is inferred by the
+    /// type checker, `_` is never bound.
+    #[must_use]
+    pub fn bind_pattern_var(&mut self, binding: &Symbol, loc: &Span) -> Vec<Diag> {
+        let mut diags = Vec::new();
+        let name = binding.name();
+        if name == "_" {
+            return diags;
+        }
+
+        binding.define(SymbolKind::Local, None, self.local_counter);
+        self.local_counter += 1;
+
+        diags.extend(self.bind_local(name, binding.clone(), loc.clone()));
+        diags
+    }
+
+    /// Binds a local variable or argument in the top most scope. Defining it
+    /// twice in the same scope is an error but shadowing a symbol of an
+    /// enclosing scope is only linted.
+    #[must_use]
+    pub fn bind_local(&mut self, name: String, sym: Symbol, loc: Span) -> Vec<Diag> {
+        let mut diags = Vec::new();
+
+        if self.table.scope_lookup_top(&name).is_some() {
+            diags.push(self.redefined(&name, loc));
+            return diags;
+        }
+        if self.table.scope_lookup(&name).is_some() {
+            let lint = self.lint(
+                &SHADOWED_NAMES,
+                format!("the symbol `{name}` shadows a previous definition"),
+                loc.clone(),
+            );
+            if let Some(diag) = lint {
+                let diag = diag.with_label(format!("`{name}` shadows a previous definition"));
+                diags.push(match self.table.def_loc(&name) {
+                    Some(prev) => diag.with_span_label(
+                        prev.clone(),
+                        format!("previous definition of `{name}` here"),
+                    ),
+                    None => diag,
+                });
+            }
+        }
+
+        // the top most scope doesn't define the name, it cannot fail.
+        self.table.scope_shadow_at(name, sym, loc).unwrap();
+        diags
+    }
+
+    /// Records that an error node was met, the parser already reported the
+    /// error so nothing else is reported about it.
+    fn error_node(&mut self) {
+        self.has_error_nodes = true;
+        self.fun_has_error_nodes = true;
+    }
+
+    /// Reports the local variables and arguments of the top most scope that
+    /// were never used, unless their name starts with `_`.
+    #[must_use]
+    pub fn report_unused_locals(&self) -> Vec<Diag> {
+        if self.fun_has_error_nodes {
+            return Vec::new();
+        }
+        self.table
+            .unused_top()
+            .into_iter()
+            .filter(|(name, _)| !name.starts_with('_'))
+            .filter_map(|(name, loc)| {
+                let diag = self.lint(
+                    &UNUSED_VARIABLES,
+                    format!("unused variable: `{name}`"),
+                    loc.clone(),
+                )?;
+                Some(diag.with_suggestion(
+                    loc.clone(),
+                    "if this is intentional, prefix it with an underscore",
+                    format!("_{name}"),
+                    Applicability::MachineApplicable,
+                ))
+            })
+            .collect()
+    }
+
+    /// Reports the private functions never used in the module, except `main`.
+    #[must_use]
+    pub fn report_unused_functions(&self) -> Vec<Diag> {
+        let mut diags = Vec::new();
+        if self.has_error_nodes {
+            return diags;
+        }
+        for decl in self.ast.iter() {
+            let DeclarationInner::Function { name, name_loc, .. } = &decl.decl else {
+                continue;
+            };
+            if decl.vis() == Visibility::Public
+                || name == "main"
+                || name.starts_with('_')
+                || self.table.is_used(name)
+            {
+                continue;
+            }
+            diags.extend(self.lint(
+                &UNUSED_FUNCTIONS,
+                format!("function `{name}` is never used"),
+                name_loc.clone(),
+            ));
+        }
+        diags
+    }
+
+    /// Checks that the types named in the type exist, and writes the module
+    /// declaring them in the type.
+    #[must_use]
+    pub fn resolve_ty(&self, ty: &Type) -> Vec<Diag> {
+        let mut diags = Vec::new();
+        match &ty.ty {
+            TypeInner::Named { name, module } => match self.resolve_type_name(name, ty.loc.clone())
+            {
+                Ok(id) => *module.borrow_mut() = Some(id),
+                Err(diag) => diags.push(diag),
+            },
+            TypeInner::FnPtr { args, ret } => {
+                for arg in args {
+                    diags.extend(self.resolve_ty(&arg.ty));
+                }
+                if let Some(ret) = ret {
+                    diags.extend(self.resolve_ty(ret));
+                }
+            }
+            TypeInner::Array { elem, .. } | TypeInner::Slice(elem) => {
+                diags.extend(self.resolve_ty(elem));
+            }
+            _ => {}
+        }
+        diags
+    }
+
+    /// Checks that the type named `name` is declared in the module or
+    /// publicly in exactly one imported module, and returns the index of
+    /// the module declaring it.
+    pub fn resolve_type_name(&self, name: &str, loc: Span) -> Result<usize, Diag> {
+        match self.lookup_type(name) {
+            Imported::Public((module, _)) => Ok(module),
+            Imported::NotFound => {
+                let mut diag = self
+                    .dcx
+                    .struct_err(
+                        format!("cannot find type `{name}` in this scope"),
+                        loc.clone(),
+                    )
+                    .with_code(E0014);
+                if let Some(similar) = self.similar_type(name) {
+                    diag = diag.with_suggestion(
+                        loc,
+                        "a type with a similar name exists",
+                        similar,
+                        Applicability::MaybeIncorrect,
+                    );
+                }
+                Err(diag)
+            }
+            Imported::Private(exports, decl) => Err(self.private_item(name, exports, decl, loc)),
+            Imported::Ambiguous(modules) => Err(self.ambiguous_item("type", name, &modules, loc)),
+        }
+    }
+
+    /// Returns the name of the value visible in the scope, or public in an
+    /// imported module, closest to the misspelled `name`.
+    pub fn similar_value(&self, name: &str) -> Option<String> {
+        let imported = self.imports.iter().flat_map(|exports| {
+            exports
+                .symbols
+                .iter()
+                .filter(|(_, (_, decl))| decl.vis() == Visibility::Public)
+                .map(|(name, _)| name.as_str())
+        });
+        let candidates = self
+            .table
+            .names()
+            .chain(imported)
+            .chain(BUILTINS.iter().copied());
+        find_best_match(name, candidates).map(str::to_string)
+    }
+
+    /// Returns the name of the type declared, public in an imported module or
+    /// primitive, closest to the misspelled `name`.
+    pub fn similar_type(&self, name: &str) -> Option<String> {
+        let imported = self.imports.iter().flat_map(|exports| {
+            exports
+                .types
+                .iter()
+                .filter(|(_, decl)| decl.vis() == Visibility::Public)
+                .map(|(name, _)| name.as_str())
+        });
+        let candidates = self
+            .types
+            .keys()
+            .map(String::as_str)
+            .chain(imported)
+            .chain(TypeInner::PRIMITIVE_TYPES);
+        find_best_match(name, candidates).map(str::to_string)
+    }
+
+    /// Returns the error of a symbol defined at `loc` while a symbol with
+    /// the same name is already visible, pointing at both definitions.
+    pub fn redefined(&self, name: &str, loc: Span) -> Diag {
+        let diag = self
+            .dcx
+            .struct_err(
+                format!("the symbol '{name}' is defined multiple times"),
+                loc,
+            )
+            .with_code(E0015)
+            .with_label(format!("`{name}` redefined here"));
+
+        match self.table.def_loc(name) {
+            Some(prev) => diag.with_span_label(
+                prev.clone(),
+                format!("previous definition of `{name}` here"),
+            ),
+            None => diag,
+        }
+    }
+
+    /// Returns the error of a use of the private item `name` of an imported
+    /// module, pointing at the use and at the declaration in the module.
+    pub fn private_item(
+        &self,
+        name: &str,
+        exports: &ModuleExports<'r>,
+        decl: &Declaration,
+        loc: Span,
+    ) -> Diag {
+        let label = match &decl.decl {
+            DeclarationInner::Enum {
+                name: enum_name, ..
+            } if enum_name != name => {
+                format!("`{name}` is a variant of the private enum `{enum_name}`")
+            }
+            _ => format!("`{name}` is declared here without `pub`"),
+        };
+
+        self.dcx
+            .struct_err(
+                format!("`{name}` is private to the module `{}`", exports.module),
+                loc,
+            )
+            .with_code(E0017)
+            .with_label("private item")
+            .with_span_label(decl.name_loc(), label)
+            .with_help(format!(
+                "add `pub` to the declaration to use `{name}` in other modules"
+            ))
+    }
+
+    /// Returns the error of a use of the item `name` declared in several
+    /// imported modules.
+    pub fn ambiguous_item(&self, what: &str, name: &str, modules: &[&str], loc: Span) -> Diag {
+        self.dcx
+            .struct_err(
+                format!(
+                    "the {what} `{name}` is ambiguous, it is defined in the modules {}",
+                    modules
+                        .iter()
+                        .map(|m| format!("`{m}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                loc,
+            )
+            .with_code(E0018)
+    }
 }
@@ -2,7 +2,7 @@
 //! it cleaner.
 
 // General analysis tools
-pub use crate::{SemanticAnalyzer, SymTabError, SymbolTable};
+pub use crate::{Imported, ModuleExports, SemanticAnalyzer, SymTabError, SymbolTable};
 
 // Other crates preludes
 pub(crate) use rosa_comm::prelude::*;
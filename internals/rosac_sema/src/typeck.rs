@@ -0,0 +1,1099 @@
+//! Module responsible for implementing type checking methods on the Semantic
+//! Analyzer.
+//!
+//! # Type Checking
+//!
+//! The type checking is done after the name resolution, so every symbol of
+//! the AST is already defined and carries its type. The type of an expression
+//! is computed from the bottom up, and when it cannot be known, because of a
+//! previous error or because the code diverges (e.g: a block ending with a
+//! return statement) `None` is returned and no more diagnostics are emitted
+//! about it.
+//!
+//! When the type an expression should have is known, e.g: the value of a let
+//! statement with a type annotation, it is passed down as a hint, that's how
+//! integer literals get their type. Otherwise the types are inferred, see the
+//! [`infer`] module.
+//!
+//! [`infer`]: crate::infer
+
+use std::{cell::RefCell, collections::HashSet};
+
+use rosac_parser::symbol::SymbolInner;
+
+use crate::prelude::*;
+
+impl<'r> SemanticAnalyzer<'r> {
+    #[must_use]
+    pub fn check_types(&mut self) -> Vec<Diag> {
+        let mut diags = Vec::new();
+
+        for decl in self.ast.iter() {
+            self.check_decl(decl, &mut diags);
+        }
+
+        diags
+    }
+
+    pub fn check_decl(&mut self, decl: &'r Declaration, diags: &mut Vec<Diag>) {
+        match &decl.decl {
+            DeclarationInner::Function { ret, block, .. } => {
+                self.fun_ret = ret.clone().map(|t| t.ty).unwrap_or(TypeInner::Void);
+                let hint = ret.as_ref().map(|t| t.ty.clone());
+                let ty = self.check_block(block, hint.as_ref(), diags);
+
+                // a function without return type may discard the value of its
+                // block.
+                if let (Some(ret), Some(ty)) = (ret, ty) {
+                    if !self.infcx.unify(&ret.ty, &ty) {
+                        diags.push(self.mismatched_types(&ret.ty, &ty, block_value_loc(block)));
+                    }
+                }
+
+                self.write_back_inferred_tys(diags);
+            }
+            // the types of the fields and of the payloads are checked by the
+            // name resolution.
+            DeclarationInner::Object { .. } | DeclarationInner::Enum { .. } => {
+                self.check_recursive_type(decl, diags);
+            }
+            DeclarationInner::Import { .. } | DeclarationInner::Error { .. } => {}
+        }
+    }
+
+    /// Type checks every statement of the block and returns the type of the
+    /// value of the block, see [`Block::value`].
+    pub fn check_block(
+        &mut self,
+        block: &'r Block<Statement>,
+        hint: Option<&TypeInner>,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        let mut ty = Some(TypeInner::Void);
+        let last = block.content.len().saturating_sub(1);
+        for (i, stmt) in block.content.iter().enumerate() {
+            // only the value of the block may take advantage of the hint.
+            let hint = if i == last { hint } else { None };
+            ty = self.check_stmt(stmt, hint, diags);
+        }
+        ty
+    }
+
+    /// Type checks the statement and returns the type of the value it
+    /// produces, only expression statements produce a value.
+    pub fn check_stmt(
+        &mut self,
+        stmt: &'r Statement,
+        hint: Option<&TypeInner>,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        match &stmt.stmt {
+            StatementInner::LetStmt {
+                symbol, ty, value, ..
+            } => {
+                match ty {
+                    Some(ty) => {
+                        self.check_expr(value, &ty.ty, diags);
+                    }
+                    // without type annotation, the variable takes the type of
+                    // its value.
+                    None => {
+                        if let Some(value_ty) = self.type_of_expr(value, None, diags) {
+                            self.infer_local_ty(symbol, value_ty);
+                        }
+                    }
+                }
+                stmt_ty(value)
+            }
+            StatementInner::ExprStmt(expr) => self.type_of_expr(expr, hint, diags),
+            StatementInner::AssignStmt { place, value } => {
+                if !is_mutable_place(place) {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                match symbol_name(place) {
+                                    Some(name) => {
+                                        format!("cannot assign to `{name}`, it is not mutable")
+                                    }
+                                    None => "cannot assign to this expression".to_string(),
+                                },
+                                place.loc.clone(),
+                            )
+                            .with_code(E0020),
+                    );
+                }
+                if let Some(place_ty) = self.type_of_expr(place, None, diags) {
+                    self.check_expr(value, &place_ty, diags);
+                } else {
+                    self.type_of_expr(value, None, diags);
+                }
+                stmt_ty(value)
+            }
+            StatementInner::ReturnStmt(value) => {
+                let ret = self.fun_ret.clone();
+                match value {
+                    Some(value) => {
+                        self.check_expr(value, &ret, diags);
+                    }
+                    None if ret != TypeInner::Void => {
+                        diags.push(self.mismatched_types(&ret, &TypeInner::Void, stmt.loc.clone()))
+                    }
+                    None => {}
+                }
+                // the code after a return statement is never reached.
+                None
+            }
+            // the type of a statement that failed to parse is unknown, it is
+            // never a mismatch.
+            StatementInner::Error => None,
+        }
+    }
+
+    /// Computes the type of the expression and emits an error if it is not
+    /// the expected type.
+    pub fn check_expr(
+        &mut self,
+        expr: &'r Expression,
+        expected: &TypeInner,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        let ty = self.type_of_expr(expr, Some(expected), diags)?;
+
+        // an array may be used where a slice of its elements is expected.
+        if let (TypeInner::Slice(elem), TypeInner::Array { elem: found, .. }) =
+            (self.infcx.resolve(expected), self.infcx.resolve(&ty))
+        {
+            if self.infcx.unify(&elem.ty, &found.ty) {
+                return Some(self.infcx.resolve(expected));
+            }
+        }
+
+        if !self.infcx.unify(expected, &ty) {
+            diags.push(self.mismatched_types(expected, &ty, expr.loc.clone()));
+            return None;
+        }
+        Some(self.infcx.resolve(&ty))
+    }
+
+    /// Computes the type of the expression, the hint is the type the
+    /// expression is expected to have, if it is known.
+    pub fn type_of_expr(
+        &mut self,
+        expr: &'r Expression,
+        hint: Option<&TypeInner>,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        match &expr.expr {
+            ExpressionInner::Error => None,
+            ExpressionInner::IntLiteral { .. } => self.type_of_int_lit(expr, hint, None),
+            ExpressionInner::BoolLiteral(_) => Some(TypeInner::Bool),
+            ExpressionInner::CharLiteral(_) => Some(TypeInner::Char),
+            ExpressionInner::StrLiteral(_) => Some(TypeInner::String),
+            ExpressionInner::ArrayLiteral(elems) => {
+                self.type_of_array_lit(expr, elems, hint, diags)
+            }
+            ExpressionInner::SymbolExpr(symbol) => match &*symbol.s.borrow() {
+                SymbolInner::Defined {
+                    kind: SymbolKind::Builtin,
+                    name,
+                    ..
+                } => {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!("the built-in function `{name}` can only be called"),
+                                expr.loc.clone(),
+                            )
+                            .with_code(E0021),
+                    );
+                    None
+                }
+                SymbolInner::Defined { ty: Some(ty), .. } => Some(ty.ty.clone()),
+                SymbolInner::Defined {
+                    ty: None,
+                    kind: SymbolKind::Local | SymbolKind::MutLocal,
+                    which,
+                    ..
+                } => {
+                    let ty = self.local_tys.get(which).cloned();
+                    // the type of the variable is not known if its value had
+                    // an error.
+                    if ty.is_some() {
+                        self.inferred_syms.push(symbol);
+                    }
+                    ty
+                }
+                // only local variables may have their type inferred, the
+                // others are the functions that failed to parse.
+                SymbolInner::Defined { ty: None, .. } => None,
+                // an error was already emitted by the name resolution.
+                SymbolInner::Undefined(_) => None,
+            },
+            ExpressionInner::BinaryExpr { lhs, op, rhs } => {
+                self.type_of_binary_expr(expr, lhs, op, rhs, hint, diags)
+            }
+            ExpressionInner::UnaryExpr { op, operand } => {
+                let ty = match (op, &operand.expr) {
+                    // the value of a negated integer literal is negative, so
+                    // it is range checked as such.
+                    (UnaryOp::Negation, ExpressionInner::IntLiteral { .. }) => {
+                        self.type_of_int_lit(operand, hint, Some(expr))
+                    }
+                    _ => self.type_of_expr(operand, hint, diags),
+                }?;
+                let ty = self.infcx.resolve(&ty);
+                let valid = match op {
+                    UnaryOp::Negation => ty.is_signed() || matches!(ty, TypeInner::IntVar(_)),
+                    UnaryOp::Not => ty == TypeInner::Bool,
+                };
+                if !valid {
+                    if let ExpressionInner::IntLiteral { .. } = operand.expr {
+                        // the negated literal doesn't need to be range
+                        // checked, the error about the operator is enough.
+                        self.int_lits.pop();
+                    }
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!("cannot apply unary operator `{op}` to type `{ty}`"),
+                                expr.loc.clone(),
+                            )
+                            .with_code(E0022),
+                    );
+                    return None;
+                }
+                // the type may be inferred unsigned later in the function.
+                if let (UnaryOp::Negation, TypeInner::IntVar(_), false) = (
+                    op,
+                    &ty,
+                    matches!(operand.expr, ExpressionInner::IntLiteral { .. }),
+                ) {
+                    self.negations.push((expr, ty.clone()));
+                }
+                Some(ty)
+            }
+            ExpressionInner::CallExpr { callee, args } => {
+                if let Some(name) = builtin_name(callee) {
+                    return self.type_of_builtin_call(expr, &name, args, diags);
+                }
+
+                let callee_ty = self
+                    .type_of_expr(callee, None, diags)
+                    .map(|t| self.infcx.resolve(&t));
+                let Some(TypeInner::FnPtr { args: params, ret }) = callee_ty else {
+                    if let Some(ty) = callee_ty {
+                        diags.push(
+                            self.dcx
+                                .struct_err(
+                                    format!("expected function, found `{ty}`"),
+                                    callee.loc.clone(),
+                                )
+                                .with_code(E0023),
+                        );
+                    }
+                    // we still type check the arguments
+                    for arg in args {
+                        self.type_of_expr(arg, None, diags);
+                    }
+                    return None;
+                };
+
+                if params.len() != args.len() {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!(
+                                    "this function takes {} argument{} but {} {} supplied",
+                                    params.len(),
+                                    if params.len() == 1 { "" } else { "s" },
+                                    args.len(),
+                                    if args.len() == 1 { "was" } else { "were" },
+                                ),
+                                expr.loc.clone(),
+                            )
+                            .with_code(E0024),
+                    );
+                }
+
+                for (i, arg) in args.iter().enumerate() {
+                    match params.get(i) {
+                        Some(param) => {
+                            // a `var` argument is passed by reference, so the
+                            // callee may write to it.
+                            if param.var && !is_mutable_place(arg) {
+                                diags.push(self.dcx.struct_err(
+                                    match symbol_name(arg) {
+                                        Some(name) => format!(
+                                            "cannot pass `{name}` as a `var` argument, it is not mutable"
+                                        ),
+                                        None => "a `var` argument must be a mutable place"
+                                            .to_string(),
+                                    },
+                                    arg.loc.clone(),
+                                ).with_code(E0020));
+                            }
+                            self.check_expr(arg, &param.ty.ty, diags)
+                        }
+                        None => self.type_of_expr(arg, None, diags),
+                    };
+                }
+
+                Some(ret.map(|t| t.ty).unwrap_or(TypeInner::Void))
+            }
+            ExpressionInner::FieldExpr {
+                expr: object,
+                field,
+            } => {
+                let ty = self.type_of_expr(object, None, diags)?;
+                let ty = self.infcx.resolve(&ty);
+                // an error was already emitted by the name resolution if the
+                // type doesn't exist.
+                if let TypeInner::Named { module, .. } = &ty {
+                    module.borrow().as_ref()?;
+                }
+                let found = self
+                    .fields_of(&ty)
+                    .and_then(|fields| fields.iter().find(|f| &f.name == field))
+                    .map(|f| f.ty.ty.clone());
+                if found.is_none() {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!("no field `{field}` on type `{ty}`"),
+                                expr.loc.clone(),
+                            )
+                            .with_code(E0025),
+                    );
+                }
+                found
+            }
+            ExpressionInner::ObjectExpr { name, fields } => {
+                self.type_of_object_expr(expr, name, fields, diags)
+            }
+            ExpressionInner::IndexExpr { expr: base, index } => {
+                let base_ty = self
+                    .type_of_expr(base, None, diags)
+                    .map(|t| self.infcx.resolve(&t));
+                // the index of a literal is an uint.
+                let index_ty = self
+                    .type_of_expr(index, Some(&TypeInner::UInt), diags)
+                    .map(|t| self.infcx.resolve(&t));
+                let base_ty = base_ty?;
+
+                let elem = match &base_ty {
+                    TypeInner::Array { elem, .. } | TypeInner::Slice(elem) => elem.ty.clone(),
+                    _ => {
+                        diags.push(
+                            self.dcx
+                                .struct_err(
+                                    format!("cannot index into a value of type `{base_ty}`"),
+                                    base.loc.clone(),
+                                )
+                                .with_code(E0026),
+                        );
+                        return None;
+                    }
+                };
+                match index_ty {
+                    Some(index_ty) if !index_ty.is_integer() => {
+                        diags.push(
+                            self.dcx
+                                .struct_err(
+                                    format!(
+                                        "the type `{base_ty}` cannot be indexed by `{index_ty}`"
+                                    ),
+                                    index.loc.clone(),
+                                )
+                                .with_code(E0026),
+                        );
+                    }
+                    _ => {}
+                }
+                // the length of an array is known, so a constant index is
+                // bounds checked now.
+                if let (TypeInner::Array { len, .. }, ExpressionInner::IntLiteral { value, .. }) =
+                    (&base_ty, &index.expr)
+                {
+                    if value >= len {
+                        diags.push(
+                            self.dcx
+                                .struct_err(
+                                    format!(
+                                "index out of bounds, the length is {len} but the index is {value}"
+                            ),
+                                    expr.loc.clone(),
+                                )
+                                .with_code(E0027),
+                        );
+                    }
+                }
+                Some(elem)
+            }
+            ExpressionInner::MatchExpr { scrutinee, arms } => {
+                self.type_of_match_expr(scrutinee, arms, hint, diags)
+            }
+            ExpressionInner::IfExpr {
+                predicate,
+                body,
+                else_branch,
+            } => {
+                self.check_expr(predicate, &TypeInner::Bool, diags);
+                let body_ty = self.check_block(body, hint, diags);
+
+                let Some(else_branch) = else_branch else {
+                    // without an else branch, the if expression cannot
+                    // produce a value.
+                    return Some(TypeInner::Void);
+                };
+                let else_ty = self.check_block(else_branch, hint.or(body_ty.as_ref()), diags);
+
+                match (body_ty, else_ty) {
+                    (Some(body_ty), Some(else_ty)) if !self.infcx.unify(&body_ty, &else_ty) => {
+                        let (body_ty, else_ty) =
+                            (self.infcx.resolve(&body_ty), self.infcx.resolve(&else_ty));
+                        diags.push(self.dcx.struct_spans_err(
+                            format!(
+                                "`if` and `else` have incompatible types, expected `{body_ty}`, found `{else_ty}`"
+                            ),
+                            vec![block_value_loc(body), block_value_loc(else_branch)],
+                        ).with_code(E0019));
+                        None
+                    }
+                    (body_ty, else_ty) => body_ty.or(else_ty).map(|t| self.infcx.resolve(&t)),
+                }
+            }
+        }
+    }
+
+    /// Computes the type of the integer literal, it's the hint if it is an
+    /// integer type or else a new integer variable. The literal is then range
+    /// checked at the end of the function, see
+    /// [`SemanticAnalyzer::write_back_inferred_tys`]. `negation` is the
+    /// expression negating the literal, if any.
+    pub fn type_of_int_lit(
+        &mut self,
+        lit: &'r Expression,
+        hint: Option<&TypeInner>,
+        negation: Option<&'r Expression>,
+    ) -> Option<TypeInner> {
+        let ty = match hint.map(|t| self.infcx.resolve(t)) {
+            Some(ty) if ty.is_integer() => ty,
+            _ => self.infcx.new_int_var(),
+        };
+        self.int_lits.push((lit, ty.clone(), negation));
+        Some(ty)
+    }
+
+    pub fn type_of_binary_expr(
+        &mut self,
+        expr: &'r Expression,
+        lhs: &'r Expression,
+        op: &BinaryOp,
+        rhs: &'r Expression,
+        hint: Option<&TypeInner>,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        // the hint is only relevant to the operands of an arithmetic operator
+        // because a comparison always produces a boolean.
+        let hint = if op.is_arithmetic() { hint } else { None };
+
+        let lhs_ty = self.type_of_expr(lhs, hint, diags);
+        let rhs_ty = self.type_of_expr(rhs, lhs_ty.as_ref(), diags);
+        let (lhs_ty, rhs_ty) = (lhs_ty?, rhs_ty?);
+
+        // the operands must have the same type, it's checked before the
+        // operator so that `1 + a` is known to have the type of `a`.
+        if !self.infcx.unify(&lhs_ty, &rhs_ty) {
+            let lhs_ty = self.infcx.resolve(&lhs_ty);
+            if op.is_arithmetic() && !operator_accepts(op, &lhs_ty) {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            format!("cannot apply binary operator `{op}` to type `{lhs_ty}`"),
+                            expr.loc.clone(),
+                        )
+                        .with_code(E0022),
+                );
+            } else {
+                diags.push(self.mismatched_types(&lhs_ty, &rhs_ty, rhs.loc.clone()));
+            }
+            return None;
+        }
+        let lhs_ty = self.infcx.resolve(&lhs_ty);
+
+        if !operator_accepts(op, &lhs_ty) {
+            diags.push(
+                self.dcx
+                    .struct_err(
+                        format!("cannot apply binary operator `{op}` to type `{lhs_ty}`"),
+                        expr.loc.clone(),
+                    )
+                    .with_code(E0022),
+            );
+            return None;
+        }
+
+        if op.is_comparison() {
+            Some(TypeInner::Bool)
+        } else {
+            Some(lhs_ty)
+        }
+    }
+
+    /// Type checks the construction of an object, every field of the object
+    /// must be initialized exactly once.
+    pub fn type_of_object_expr(
+        &mut self,
+        expr: &'r Expression,
+        name: &str,
+        fields: &'r [(String, Expression)],
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        // an error was already emitted by the name resolution if the type
+        // doesn't exist.
+        let ty = match self.lookup_type(name) {
+            Imported::Public((module, _)) => Some(TypeInner::Named {
+                name: name.to_string(),
+                module: RefCell::new(Some(module)),
+            }),
+            _ => None,
+        };
+        let Some(decl_fields) = ty.as_ref().and_then(|ty| self.fields_of(ty)) else {
+            for (_, value) in fields {
+                self.type_of_expr(value, None, diags);
+            }
+            return None;
+        };
+
+        for (i, (field, value)) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|(f, _)| f == field) {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            format!("field `{field}` specified more than once"),
+                            value.loc.clone(),
+                        )
+                        .with_code(E0028),
+                );
+            }
+            match decl_fields.iter().find(|f| &f.name == field) {
+                Some(decl_field) => {
+                    self.check_expr(value, &decl_field.ty.ty, diags);
+                }
+                None => {
+                    diags.push(
+                        self.dcx
+                            .struct_err(
+                                format!("no field named `{field}` in `{name}`"),
+                                value.loc.clone(),
+                            )
+                            .with_code(E0029),
+                    );
+                    self.type_of_expr(value, None, diags);
+                }
+            }
+        }
+
+        let missing = decl_fields
+            .iter()
+            .filter(|f| !fields.iter().any(|(field, _)| field == &f.name))
+            .map(|f| format!("`{}`", f.name))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            diags.push(
+                self.dcx
+                    .struct_err(
+                        format!(
+                            "missing field{} {} in initializer of `{name}`",
+                            if missing.len() == 1 { "" } else { "s" },
+                            missing.join(", "),
+                        ),
+                        expr.loc.clone(),
+                    )
+                    .with_code(E0030),
+            );
+        }
+
+        ty
+    }
+
+    /// Type checks the array literal, all its elements must have the same
+    /// type.
+    pub fn type_of_array_lit(
+        &mut self,
+        expr: &'r Expression,
+        elems: &'r [Expression],
+        hint: Option<&TypeInner>,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        let elem_hint = match hint.map(|t| self.infcx.resolve(t)) {
+            Some(TypeInner::Array { elem, .. } | TypeInner::Slice(elem)) => Some(elem.ty),
+            _ => None,
+        };
+
+        let Some(first) = elems.first() else {
+            if elem_hint.is_none() {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            "cannot infer the type of an empty array literal",
+                            expr.loc.clone(),
+                        )
+                        .with_code(E0031),
+                );
+            }
+            return elem_hint.map(|elem| array_of(elem, 0));
+        };
+
+        let Some(elem) = self.type_of_expr(first, elem_hint.as_ref(), diags) else {
+            for elem in &elems[1..] {
+                self.type_of_expr(elem, elem_hint.as_ref(), diags);
+            }
+            return None;
+        };
+        for other in &elems[1..] {
+            self.check_expr(other, &elem, diags);
+        }
+
+        Some(array_of(self.infcx.resolve(&elem), elems.len() as u64))
+    }
+
+    /// Type checks the match expression, the scrutinee must be an enum and
+    /// every variant must be covered by an arm.
+    pub fn type_of_match_expr(
+        &mut self,
+        scrutinee: &'r Expression,
+        arms: &'r [MatchArm],
+        hint: Option<&TypeInner>,
+        diags: &mut Vec<Diag>,
+    ) -> Option<TypeInner> {
+        let scrutinee_ty = self
+            .type_of_expr(scrutinee, None, diags)
+            .map(|t| self.infcx.resolve(&t));
+        let variants = match &scrutinee_ty {
+            Some(ty) => self.variants_of(ty),
+            None => None,
+        };
+        if let (Some(ty), None) = (&scrutinee_ty, variants) {
+            diags.push(
+                self.dcx
+                    .struct_err(
+                        format!("cannot match on type `{ty}`, only enums can be matched"),
+                        scrutinee.loc.clone(),
+                    )
+                    .with_code(E0032),
+            );
+        }
+
+        let mut covered = vec![false; variants.map_or(0, |v| v.len())];
+        let mut has_wildcard = false;
+        // the type of the match and the arm it comes from.
+        let mut first: Option<(TypeInner, &'r MatchArm)> = None;
+
+        for arm in arms {
+            match (&arm.pattern.pat, variants) {
+                (PatternInner::Wildcard, _) => has_wildcard = true,
+                (PatternInner::Variant { name, bindings }, Some(variants)) => {
+                    match variants.iter().position(|v| &v.name == name) {
+                        Some(i) => {
+                            covered[i] = true;
+                            let payload = &variants[i].payload;
+                            if payload.len() != bindings.len() {
+                                diags.push(self.dcx.struct_err(
+                                    format!(
+                                        "this pattern has {} field{}, but the variant `{name}` has {} field{}",
+                                        bindings.len(),
+                                        if bindings.len() == 1 { "" } else { "s" },
+                                        payload.len(),
+                                        if payload.len() == 1 { "" } else { "s" },
+                                    ),
+                                    arm.pattern.loc.clone(),
+                                ).with_code(E0033));
+                            }
+                            for ((binding, _), ty) in bindings.iter().zip(payload) {
+                                self.infer_local_ty(binding, ty.ty.clone());
+                            }
+                        }
+                        None => {
+                            // we know the scrutinee has a named type.
+                            let ty = scrutinee_ty.as_ref().unwrap();
+                            diags.push(
+                                self.dcx
+                                    .struct_err(
+                                        format!("no variant named `{name}` in `{ty}`"),
+                                        arm.pattern.loc.clone(),
+                                    )
+                                    .with_code(E0034),
+                            );
+                        }
+                    }
+                }
+                // an error was already emitted about the scrutinee.
+                (PatternInner::Variant { .. }, None) => {}
+            }
+
+            let hint = hint.or(first.as_ref().map(|(t, _)| t));
+            let Some(arm_ty) = self.check_block(&arm.body, hint, diags) else {
+                continue;
+            };
+            match &first {
+                Some((first_ty, first_arm)) if !self.infcx.unify(first_ty, &arm_ty) => {
+                    let (first_ty, arm_ty) =
+                        (self.infcx.resolve(first_ty), self.infcx.resolve(&arm_ty));
+                    diags.push(self.dcx.struct_spans_err(
+                        format!(
+                            "`match` arms have incompatible types, expected `{first_ty}`, found `{arm_ty}`"
+                        ),
+                        vec![block_value_loc(&first_arm.body), block_value_loc(&arm.body)],
+                    ).with_code(E0019));
+                }
+                Some(_) => {}
+                None => first = Some((arm_ty, arm)),
+            }
+        }
+
+        if let (Some(variants), false) = (variants, has_wildcard) {
+            let missing = variants
+                .iter()
+                .zip(&covered)
+                .filter(|(_, covered)| !**covered)
+                .map(|(v, _)| variant_pattern(v))
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            format!(
+                                "non-exhaustive patterns, {} not covered",
+                                join_and(&missing)
+                            ),
+                            scrutinee.loc.clone(),
+                        )
+                        .with_code(E0035),
+                );
+            }
+        }
+
+        first.map(|(ty, _)| self.infcx.resolve(&ty))
+    }
+
+    /// Returns the declaration of the named type `ty`, `None` if it's not a
+    /// named type or if it wasn't resolved.
+    pub fn decl_of(&self, ty: &TypeInner) -> Option<&'r Declaration> {
+        match ty {
+            TypeInner::Named { name, module } => self.type_decl((*module.borrow())?, name),
+            _ => None,
+        }
+    }
+
+    /// Returns the type with the names of the types declared in the imported
+    /// modules qualified by the module, e.g: `geo::Point`, so they can be
+    /// told apart from the types with the same name in the messages.
+    pub fn qualified(&self, ty: &TypeInner) -> TypeInner {
+        let qualify = |ty: &Type| Type {
+            ty: self.qualified(&ty.ty),
+            loc: ty.loc.clone(),
+        };
+        match ty {
+            TypeInner::Named { name, module } => {
+                let exports = module
+                    .borrow()
+                    .filter(|&id| id != self.module)
+                    .and_then(|id| self.imports.iter().find(|i| i.id == id));
+                match exports {
+                    Some(exports) => TypeInner::Named {
+                        name: format!("{}::{name}", exports.module),
+                        module: module.clone(),
+                    },
+                    None => ty.clone(),
+                }
+            }
+            TypeInner::Array { elem, len } => TypeInner::Array {
+                elem: Box::new(qualify(elem)),
+                len: *len,
+            },
+            TypeInner::Slice(elem) => TypeInner::Slice(Box::new(qualify(elem))),
+            TypeInner::FnPtr { args, ret } => TypeInner::FnPtr {
+                args: args
+                    .iter()
+                    .map(|arg| FnPtrArg {
+                        ty: qualify(&arg.ty),
+                        var: arg.var,
+                    })
+                    .collect(),
+                ret: ret.as_deref().map(|ret| Box::new(qualify(ret))),
+            },
+            _ => ty.clone(),
+        }
+    }
+
+    /// Returns the variants of the enum type `ty`, `None` if it's not an
+    /// enum.
+    pub fn variants_of(&self, ty: &TypeInner) -> Option<&'r [Variant]> {
+        match &self.decl_of(ty)?.decl {
+            DeclarationInner::Enum { variants, .. } => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of the object type `ty`, `None` if it's not an
+    /// object.
+    pub fn fields_of(&self, ty: &TypeInner) -> Option<&'r [Field]> {
+        match &self.decl_of(ty)?.decl {
+            DeclarationInner::Object { fields, .. } => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Reports the type declaration if it contains itself, directly or
+    /// through the other types of the module, such a type would have an
+    /// infinite size.
+    pub fn check_recursive_type(&self, decl: &'r Declaration, diags: &mut Vec<Diag>) {
+        let name = match &decl.decl {
+            DeclarationInner::Object { name, .. } | DeclarationInner::Enum { name, .. } => name,
+            _ => return,
+        };
+        // the declaration defining the type again was already reported.
+        if !self.types.get(name).is_some_and(|d| std::ptr::eq(*d, decl)) {
+            return;
+        }
+
+        let mut visited = HashSet::new();
+        let mut stack = contained_types(decl);
+        while let Some(ty) = stack.pop() {
+            let TypeInner::Named {
+                name: ty_name,
+                module,
+            } = ty
+            else {
+                continue;
+            };
+            // the imports are acyclic, only the types of the module may
+            // contain this one.
+            if *module.borrow() != Some(self.module) || !visited.insert(ty_name) {
+                continue;
+            }
+            if ty_name == name {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            format!("recursive type `{name}` has infinite size"),
+                            decl.name_loc(),
+                        )
+                        .with_code(E0037)
+                        .with_label(format!("`{name}` contains itself")),
+                );
+                return;
+            }
+            if let Some(decl) = self.types.get(ty_name) {
+                stack.extend(contained_types(decl));
+            }
+        }
+    }
+
+    /// Binds the type of the local variable, it will be written in the
+    /// symbols of the variable at the end of the function.
+    pub fn infer_local_ty(&mut self, symbol: &'r Symbol, ty: TypeInner) {
+        if let SymbolInner::Defined { which, .. } = &*symbol.s.borrow() {
+            self.local_tys.insert(*which, ty);
+            self.inferred_syms.push(symbol);
+        }
+    }
+
+    /// Writes the inferred type of the local variables in their symbols and
+    /// the type of the integer literals, that are also range checked. The
+    /// integer variables that are not bound default to `int`.
+    pub fn write_back_inferred_tys(&mut self, diags: &mut Vec<Diag>) {
+        for symbol in self.inferred_syms.drain(..) {
+            let which = match &*symbol.s.borrow() {
+                SymbolInner::Defined { which, .. } => *which,
+                SymbolInner::Undefined(_) => continue,
+            };
+            let ty = self.infcx.resolve_or_default(&self.local_tys[&which]);
+            symbol.set_inferred_ty(Type {
+                ty,
+                loc: Span::ZERO,
+            });
+        }
+
+        for (lit, ty, negation) in std::mem::take(&mut self.int_lits) {
+            let ExpressionInner::IntLiteral { value, ty: lit_ty } = &lit.expr else {
+                unreachable!("only integer literals are range checked");
+            };
+            let ty = self.infcx.resolve_or_default(&ty);
+
+            let value = if negation.is_some() {
+                -(*value as i128)
+            } else {
+                *value as i128
+            };
+            // the range is always known because the type is resolved
+            let range = ty.int_range().unwrap();
+            if !range.contains(&value) {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            format!(
+                        "literal out of range for `{ty}`, `{value}` doesn't fit in `{}..={}`",
+                        range.start(),
+                        range.end()
+                    ),
+                            negation.unwrap_or(lit).loc.clone(),
+                        )
+                        .with_code(E0036),
+                );
+            }
+
+            *lit_ty.borrow_mut() = Some(ty);
+        }
+
+        for (expr, ty) in std::mem::take(&mut self.negations) {
+            let ty = self.infcx.resolve_or_default(&ty);
+            if !ty.is_signed() {
+                diags.push(
+                    self.dcx
+                        .struct_err(
+                            format!("cannot apply unary operator `-` to type `{ty}`"),
+                            expr.loc.clone(),
+                        )
+                        .with_code(E0022),
+                );
+            }
+        }
+
+        self.local_tys.clear();
+        self.infcx.clear();
+    }
+
+    pub fn mismatched_types(&self, expected: &TypeInner, found: &TypeInner, loc: Span) -> Diag {
+        let expected = self.qualified(&self.infcx.resolve(expected));
+        let found = self.qualified(&self.infcx.resolve(found));
+        self.dcx
+            .struct_err(
+                format!("mismatched types, expected `{expected}`, found `{found}`"),
+                loc,
+            )
+            .with_code(E0019)
+    }
+}
+
+/// Returns the type of a statement ending with the value, void unless the
+/// value failed to parse, then the type is unknown.
+fn stmt_ty(value: &Expression) -> Option<TypeInner> {
+    match value.expr {
+        ExpressionInner::Error => None,
+        _ => Some(TypeInner::Void),
+    }
+}
+
+/// Returns the location of the value of the block or the location of the
+/// whole block if it has no value.
+fn block_value_loc(block: &Block<Statement>) -> Span {
+    block
+        .value()
+        .map(|v| v.loc.clone())
+        .unwrap_or(block.loc.clone())
+}
+
+/// Returns the types stored in the values of the type declaration, the types
+/// of the fields or of the payloads, and of the elements of the arrays among
+/// them. The slices and the functions are not stored in the values.
+fn contained_types(decl: &Declaration) -> Vec<&TypeInner> {
+    let tys: Vec<&Type> = match &decl.decl {
+        DeclarationInner::Object { fields, .. } => fields.iter().map(|f| &f.ty).collect(),
+        DeclarationInner::Enum { variants, .. } => {
+            variants.iter().flat_map(|v| &v.payload).collect()
+        }
+        _ => Vec::new(),
+    };
+    tys.into_iter()
+        .map(|mut ty| {
+            while let TypeInner::Array { elem, .. } = &ty.ty {
+                ty = elem;
+            }
+            &ty.ty
+        })
+        .collect()
+}
+
+/// Formats the pattern matching any value of the variant, e.g: `` `Rect(_, _)` ``
+fn variant_pattern(variant: &Variant) -> String {
+    if variant.payload.is_empty() {
+        format!("`{}`", variant.name)
+    } else {
+        let wildcards = vec!["_"; variant.payload.len()];
+        format!("`{}({})`", variant.name, wildcards.join(", "))
+    }
+}
+
+/// Joins the items like in an english enumeration, e.g: `a, b and c`
+fn join_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+/// Is the expression a place that may be written to, a mutable local
+/// variable, a `var` argument or a field of one of those.
+fn is_mutable_place(expr: &Expression) -> bool {
+    match &expr.expr {
+        ExpressionInner::SymbolExpr(symbol) => match &*symbol.s.borrow() {
+            SymbolInner::Defined { kind, .. } => {
+                matches!(kind, SymbolKind::MutLocal | SymbolKind::VarArg)
+            }
+            // an error was already emitted by the name resolution.
+            SymbolInner::Undefined(_) => true,
+        },
+        ExpressionInner::FieldExpr { expr, .. } | ExpressionInner::IndexExpr { expr, .. } => {
+            is_mutable_place(expr)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the name of the symbol if the expression is just a symbol.
+fn symbol_name(expr: &Expression) -> Option<String> {
+    match &expr.expr {
+        ExpressionInner::SymbolExpr(symbol) => Some(symbol.name()),
+        _ => None,
+    }
+}
+
+/// Returns the name of the built-in function if the callee is one.
+fn builtin_name(callee: &Expression) -> Option<String> {
+    match &callee.expr {
+        ExpressionInner::SymbolExpr(symbol) => match &*symbol.s.borrow() {
+            SymbolInner::Defined {
+                kind: SymbolKind::Builtin,
+                name,
+                ..
+            } => Some(name.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn array_of(elem: TypeInner, len: u64) -> TypeInner {
+    TypeInner::Array {
+        elem: Box::new(Type {
+            ty: elem,
+            loc: Span::ZERO,
+        }),
+        len,
+    }
+}
+
+/// Can the binary operator be applied to operands of type `ty`, the strings
+/// may be concatenated with `+` and compared for equality.
+fn operator_accepts(op: &BinaryOp, ty: &TypeInner) -> bool {
+    if op.is_arithmetic() {
+        ty.is_integer() || (*op == BinaryOp::Add && *ty == TypeInner::String)
+    } else if op.is_ordering() {
+        ty.is_integer() || *ty == TypeInner::Char
+    } else {
+        *ty != TypeInner::Void
+    }
+}
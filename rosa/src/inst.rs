@@ -185,6 +185,349 @@ arith_impl! {
     CompNeInstOpcode = 28;
 }
 
+/// The call instruction, calls the function at the address in the chunk.
+///
+/// # Bytecode Layout
+///
+/// `CALL addr:dynint`
+///
+/// The opcode for the call instruction is followed by the address of the
+/// function in the chunk encoded as a dynamic integer.
+///
+/// # Stack
+///
+/// Does nothing, the arguments of the function are already on the stack.
+#[derive(Debug)]
+pub struct CallInst;
+
+impl Instruction for CallInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let addr = vm.read_dyn_int()?;
+        vm.call(addr)
+    }
+
+    fn opcode(&self) -> u8 {
+        29
+    }
+}
+
+/// The call reference instruction, calls the function pointed by a function
+/// reference.
+///
+/// # Bytecode Layout
+///
+/// `CALLREF`
+///
+/// Only the Op code.
+///
+/// # Stack
+///
+/// Pops the function reference, the address of the function in the chunk
+/// as an u64, the arguments of the function are below it.
+///
+/// # Note
+/// `rosac` doesn't emit it yet, the calls through function pointers are only
+/// type checked, there is no code generator.
+#[derive(Debug)]
+pub struct CallRefInst;
+
+impl Instruction for CallRefInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let addr: u64 = vm.stack_pop()?;
+        vm.call(addr)
+    }
+
+    fn opcode(&self) -> u8 {
+        30
+    }
+}
+
+/// The return instruction, returns from the function being executed.
+///
+/// # Bytecode Layout
+///
+/// `RET`
+///
+/// Only the Op code.
+///
+/// # Stack
+///
+/// Does nothing, the return value is left on the stack.
+#[derive(Debug)]
+pub struct RetInst;
+
+impl Instruction for RetInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        vm.ret()
+    }
+
+    fn opcode(&self) -> u8 {
+        31
+    }
+}
+
+/// The reference instruction, pushes a reference to a value on the stack.
+///
+/// # Bytecode Layout
+///
+/// `REF offset:dynint`
+///
+/// The opcode for the reference instruction is followed by the offset of the
+/// value from the top of the stack, encoded as a dynamic integer, e.g: the
+/// reference to the last byte pushed has an offset of 1.
+///
+/// # Stack
+///
+/// Push the reference, the position of the value in the stack as an u64.
+///
+/// # Note
+/// With `LOAD` and `STORE` it is meant to pass the `var` arguments, but their
+/// lowering isn't implemented, `rosac` stops after the semantic analysis.
+#[derive(Debug)]
+pub struct RefInst;
+
+impl Instruction for RefInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let offset = vm.read_dyn_int()? as usize;
+        let addr = vm.sp.checked_sub(offset).ok_or(RuntimeError::UnderFlow)?;
+        vm.stack_push(addr as u64);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        32
+    }
+}
+
+/// The load instruction, reads a value through a reference.
+///
+/// # Bytecode Layout
+///
+/// `LOAD size:dynint`
+///
+/// The opcode for the load instruction is followed by the size of the value
+/// encoded as a dynamic integer.
+///
+/// # Stack
+///
+/// Pops the reference and push a copy of the value it points to.
+#[derive(Debug)]
+pub struct LoadInst;
+
+impl Instruction for LoadInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let size = vm.read_dyn_int()? as usize;
+        let addr: u64 = vm.stack_pop()?;
+        let value = vm.deref(addr, size)?.to_owned();
+        vm.stack_push_raw(value);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        33
+    }
+}
+
+/// The store instruction, writes a value through a reference.
+///
+/// # Bytecode Layout
+///
+/// `STORE size:dynint`
+///
+/// The opcode for the store instruction is followed by the size of the value
+/// encoded as a dynamic integer.
+///
+/// # Stack
+///
+/// Pops the value and then the reference, and writes the value where the
+/// reference points.
+#[derive(Debug)]
+pub struct StoreInst;
+
+impl Instruction for StoreInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let size = vm.read_dyn_int()? as usize;
+        let value = vm.stack_pop_raw(size)?.to_owned();
+        let addr: u64 = vm.stack_pop()?;
+        vm.store(addr, &value)
+    }
+
+    fn opcode(&self) -> u8 {
+        34
+    }
+}
+
+/// The index instruction, computes the reference to an element of a slice,
+/// the index is bounds checked.
+///
+/// # Bytecode Layout
+///
+/// `INDEX size:dynint`
+///
+/// The opcode for the index instruction is followed by the size of the
+/// elements encoded as a dynamic integer.
+///
+/// # Stack
+///
+/// Pops the index, then the slice, its length and then the reference to its
+/// first element, all as u64, and push the reference to the element.
+///
+/// # Note
+/// The indexing expressions are not lowered to it yet, `rosac` doesn't
+/// generate bytecode.
+#[derive(Debug)]
+pub struct IndexInst;
+
+impl Instruction for IndexInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let size = vm.read_dyn_int()?;
+        let index: u64 = vm.stack_pop()?;
+        let len: u64 = vm.stack_pop()?;
+        let addr: u64 = vm.stack_pop()?;
+        if index >= len {
+            return Err(RuntimeError::IndexOutOfBounds { index, len });
+        }
+        let elem = index
+            .checked_mul(size)
+            .and_then(|offset| addr.checked_add(offset))
+            .ok_or(RuntimeError::ArithmeticError {
+                msg: "reference to the element with overflow",
+            })?;
+        vm.stack_push(elem);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        35
+    }
+}
+
+/// The string instruction, creates a string from a constant of the pool.
+///
+/// # Bytecode Layout
+///
+/// `STR offset:dynint`
+///
+/// The opcode for the string instruction is followed by the offset of the
+/// UTF-8 string in the pool encoded as a dynamic integer.
+///
+/// # Stack
+///
+/// Push the handle of the new string.
+///
+/// # Note
+/// The string literals and the `+`, `==` and `!=` operators on strings are
+/// not lowered to the string instructions yet, there is no code generator.
+#[derive(Debug)]
+pub struct StrInst;
+
+impl Instruction for StrInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let offset: usize = vm.read_dyn_int()? as usize;
+        let data = vm
+            .pool
+            .get(offset)
+            .ok_or(RuntimeError::UnknownConst { offset })?;
+        let s =
+            String::from_utf8(data.to_owned()).map_err(|_| RuntimeError::InvalidUtf8 { offset })?;
+        let handle = vm.alloc_str(s);
+        vm.stack_push(handle);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        36
+    }
+}
+
+/// The string concatenation instruction.
+///
+/// # Bytecode Layout
+///
+/// `STRCAT`
+///
+/// Only the Op code.
+///
+/// # Stack
+///
+/// Pops the handles of the right hand side string and then of the left hand
+/// side, and push the handle of their concatenation.
+#[derive(Debug)]
+pub struct StrCatInst;
+
+impl Instruction for StrCatInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let rhs: u64 = vm.stack_pop()?;
+        let lhs: u64 = vm.stack_pop()?;
+        let s = format!("{}{}", vm.get_str(lhs)?, vm.get_str(rhs)?);
+        let handle = vm.alloc_str(s);
+        vm.stack_push(handle);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        37
+    }
+}
+
+/// The string equality instruction.
+///
+/// # Bytecode Layout
+///
+/// `STREQ`
+///
+/// Only the Op code.
+///
+/// # Stack
+///
+/// Pops the handles of two strings and push `1` if they are equal, `0`
+/// otherwise.
+#[derive(Debug)]
+pub struct StrEqInst;
+
+impl Instruction for StrEqInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let rhs: u64 = vm.stack_pop()?;
+        let lhs: u64 = vm.stack_pop()?;
+        let eq = vm.get_str(lhs)? == vm.get_str(rhs)?;
+        vm.stack_push(eq);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        38
+    }
+}
+
+/// The string inequality instruction.
+///
+/// # Bytecode Layout
+///
+/// `STRNE`
+///
+/// Only the Op code.
+///
+/// # Stack
+///
+/// Pops the handles of two strings and push `1` if they are not equal, `0`
+/// otherwise.
+#[derive(Debug)]
+pub struct StrNeInst;
+
+impl Instruction for StrNeInst {
+    fn execute(&self, vm: &mut VirtualMachine) -> Result<()> {
+        let rhs: u64 = vm.stack_pop()?;
+        let lhs: u64 = vm.stack_pop()?;
+        let ne = vm.get_str(lhs)? != vm.get_str(rhs)?;
+        vm.stack_push(ne);
+        Ok(())
+    }
+
+    fn opcode(&self) -> u8 {
+        39
+    }
+}
+
 /// An help macro used to more easily build the [instruction set] of the VM.
 ///
 /// [instruction set]: struct@crate::inst::INSTRUCTION_SET
@@ -234,5 +577,20 @@ lazy_static! {
         U16CompGTEInst,
         U16CompEqInst,
         U16CompNeInst,
+        // functions
+        CallInst,
+        CallRefInst,
+        RetInst,
+        // references
+        RefInst,
+        LoadInst,
+        StoreInst,
+        // slices
+        IndexInst,
+        // strings
+        StrInst,
+        StrCatInst,
+        StrEqInst,
+        StrNeInst,
     );
 }
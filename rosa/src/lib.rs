@@ -64,6 +64,19 @@ pub enum RuntimeError {
     /// arithmetic error, the message ('msg') explains what is the arithmetic
     /// error in question
     ArithmeticError { msg: &'static str },
+    /// tried to call a function reference that points out of the Chunk
+    InvalidFunRef { addr: u64 },
+    /// tried to return from a function but the call stack is empty
+    CallStackUnderFlow,
+    /// tried to read or write through a reference that doesn't point to a
+    /// value on the stack
+    InvalidRef { addr: u64 },
+    /// tried to index an array or a slice out of its bounds
+    IndexOutOfBounds { index: u64, len: u64 },
+    /// unknown handle in the strings of the VM
+    UnknownString { handle: u64 },
+    /// the constant at the offset in the constant pool isn't valid UTF-8
+    InvalidUtf8 { offset: usize },
 }
 
 impl Display for RuntimeError {
@@ -80,6 +93,29 @@ impl Display for RuntimeError {
             Self::ArithmeticError { msg } => {
                 write!(f, "arithmetic error: {msg}")
             }
+            Self::InvalidFunRef { addr } => {
+                write!(
+                    f,
+                    "invalid function reference ({addr:#010X?}) out of the chunk"
+                )
+            }
+            Self::CallStackUnderFlow => write!(f, "call stack under flow"),
+            Self::InvalidRef { addr } => {
+                write!(f, "invalid reference ({addr:#010X?}) out of the stack")
+            }
+            Self::IndexOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "index out of bounds, the length is {len} but the index is {index}"
+                )
+            }
+            Self::UnknownString { handle } => write!(f, "unknown string handle {handle}"),
+            Self::InvalidUtf8 { offset } => {
+                write!(
+                    f,
+                    "the constant at the offset ({offset:#010X?}) isn't a valid UTF-8 string"
+                )
+            }
         }
     }
 }
@@ -105,7 +141,16 @@ impl RuntimeError {
             writeln!(s, "  {i}: {:#04X?}", byte)?;
         }
 
-        // TODO: format the call stack
+        s.set_color(&WHITE_BOLD)?;
+        writeln!(s, "CALL STACK ({}):", vm.calls.len())?;
+        if vm.calls.is_empty() {
+            writeln!(s, "  ...")?;
+        }
+        s.reset()?;
+        for (i, ret) in vm.calls.iter().rev().enumerate() {
+            writeln!(s, "  {i}: returns to {ret:#010X?}")?;
+        }
+
         s.reset()?;
         s.flush()?;
         Ok(())
@@ -214,6 +259,15 @@ pub struct VirtualMachine {
     /// but if `Some`, stop and the value is the exit code.
     exit: Option<u8>,
     pool: ConstantPool,
+    /// the call stack, it contains the return address of each function
+    /// called, the top most is the one of the function being executed.
+    calls: Vec<usize>,
+    /// the strings created by the program, a string on the stack is its
+    /// handle, an index in this vector, as an u64.
+    ///
+    /// # Note
+    /// The strings are never freed for now.
+    strings: Vec<String>,
 }
 
 impl VirtualMachine {
@@ -245,6 +299,8 @@ impl VirtualMachine {
             sp: 0,
             exit: None,
             pool,
+            calls: Vec::new(),
+            strings: Vec::new(),
         }
     }
 
@@ -300,6 +356,59 @@ impl VirtualMachine {
         Ok(*self.stack_pop_raw(1usize)?.first().unwrap())
     }
 
+    /// Calls the function starting at `addr` in the chunk, the arguments are
+    /// left on the stack for the callee.
+    pub fn call(&mut self, addr: u64) -> Result<()> {
+        if addr >= self.program.data.len() as u64 {
+            return Err(RuntimeError::InvalidFunRef { addr });
+        }
+        self.calls.push(self.ip);
+        self.ip = addr as usize;
+        Ok(())
+    }
+
+    /// Returns from the function being executed, to the instruction following
+    /// its call.
+    pub fn ret(&mut self) -> Result<()> {
+        self.ip = self.calls.pop().ok_or(RuntimeError::CallStackUnderFlow)?;
+        Ok(())
+    }
+
+    /// Returns the `size` bytes of the stack pointed by the reference `addr`.
+    pub fn deref(&self, addr: u64, size: usize) -> Result<&[u8]> {
+        let start = addr as usize;
+        match start.checked_add(size) {
+            Some(end) if end <= self.sp => Ok(&self.stack[start..end]),
+            _ => Err(RuntimeError::InvalidRef { addr }),
+        }
+    }
+
+    /// Writes the value on the stack where the reference `addr` points.
+    pub fn store(&mut self, addr: u64, value: &[u8]) -> Result<()> {
+        let start = addr as usize;
+        match start.checked_add(value.len()) {
+            Some(end) if end <= self.sp => {
+                self.stack[start..end].copy_from_slice(value);
+                Ok(())
+            }
+            _ => Err(RuntimeError::InvalidRef { addr }),
+        }
+    }
+
+    /// Stores the string in the VM and returns its handle.
+    pub fn alloc_str(&mut self, s: String) -> u64 {
+        self.strings.push(s);
+        self.strings.len() as u64 - 1
+    }
+
+    /// Returns the string behind the handle.
+    pub fn get_str(&self, handle: u64) -> Result<&str> {
+        self.strings
+            .get(handle as usize)
+            .map(String::as_str)
+            .ok_or(RuntimeError::UnknownString { handle })
+    }
+
     /// Extends the stack to contain `amount` more bytes of free space.
     pub fn extend_stack(&mut self, amount: usize) {
         self.stack.extend(vec![0; amount]);
@@ -464,6 +573,178 @@ pub fn ones_needed(number: u64) -> u8 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn call_fun_ref() {
+        use crate::inst::{CallRefInst, ConstInst, ExitInst, Instruction, RetInst};
+
+        let chunk = Chunk::from(vec![
+            ConstInst.opcode(),
+            0,
+            CallRefInst.opcode(),
+            ExitInst.opcode(),
+            // the function, it returns 42
+            ConstInst.opcode(),
+            8,
+            RetInst.opcode(),
+        ]);
+        let mut data = 4u64.to_be_bytes().to_vec();
+        data.push(42);
+        let pool = ConstantPool::new(HashMap::from([(0, 8), (8, 1)]), data);
+
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert_eq!(vm.run().unwrap(), 42);
+    }
+
+    #[test]
+    fn call_invalid_fun_ref() {
+        use crate::inst::{CallRefInst, ConstInst, Instruction};
+
+        let chunk = Chunk::from(vec![ConstInst.opcode(), 0, CallRefInst.opcode()]);
+        let pool = ConstantPool::new(HashMap::from([(0, 8)]), 100u64.to_be_bytes().to_vec());
+
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert!(matches!(
+            vm.run(),
+            Err(RuntimeError::InvalidFunRef { addr: 100 })
+        ));
+    }
+
+    #[test]
+    fn write_through_ref() {
+        use crate::inst::{ConstInst, ExitInst, Instruction, LoadInst, RefInst, StoreInst};
+
+        let chunk = Chunk::from(vec![
+            // the variable
+            ConstInst.opcode(),
+            0,
+            // writes 7 in the variable through a reference
+            RefInst.opcode(),
+            1,
+            ConstInst.opcode(),
+            1,
+            StoreInst.opcode(),
+            1,
+            // reads the variable through a reference
+            RefInst.opcode(),
+            1,
+            LoadInst.opcode(),
+            1,
+            ExitInst.opcode(),
+        ]);
+        let pool = ConstantPool::new(HashMap::from([(0, 1), (1, 1)]), vec![5, 7]);
+
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert_eq!(vm.run().unwrap(), 7);
+    }
+
+    /// Builds a chunk indexing the array `[10, 20, 30]` with `index`.
+    fn index_chunk(index: u64) -> (Chunk, ConstantPool) {
+        use crate::inst::{ConstInst, ExitInst, IndexInst, Instruction, LoadInst, RefInst};
+
+        let chunk = Chunk::from(vec![
+            // the array
+            ConstInst.opcode(),
+            0,
+            // the slice of the array, its reference and its length
+            RefInst.opcode(),
+            3,
+            ConstInst.opcode(),
+            3,
+            // the index
+            ConstInst.opcode(),
+            11,
+            IndexInst.opcode(),
+            1,
+            LoadInst.opcode(),
+            1,
+            ExitInst.opcode(),
+        ]);
+        let mut data = vec![10, 20, 30];
+        data.extend(3u64.to_be_bytes());
+        data.extend(index.to_be_bytes());
+        let pool = ConstantPool::new(HashMap::from([(0, 3), (3, 8), (11, 8)]), data);
+        (chunk, pool)
+    }
+
+    #[test]
+    fn index_slice() {
+        let (chunk, pool) = index_chunk(2);
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert_eq!(vm.run().unwrap(), 30);
+    }
+
+    #[test]
+    fn index_slice_out_of_bounds() {
+        let (chunk, pool) = index_chunk(3);
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert!(matches!(
+            vm.run(),
+            Err(RuntimeError::IndexOutOfBounds { index: 3, len: 3 })
+        ));
+    }
+
+    #[test]
+    fn index_slice_overflow() {
+        use crate::inst::{ConstInst, IndexInst, Instruction};
+
+        // the last element of a slice of `u64::MAX` elements of 2 bytes.
+        let chunk = Chunk::from(vec![
+            ConstInst.opcode(),
+            0,
+            ConstInst.opcode(),
+            8,
+            ConstInst.opcode(),
+            16,
+            IndexInst.opcode(),
+            2,
+        ]);
+        let mut data = 0u64.to_be_bytes().to_vec();
+        data.extend(u64::MAX.to_be_bytes());
+        data.extend((u64::MAX - 1).to_be_bytes());
+        let pool = ConstantPool::new(HashMap::from([(0, 8), (8, 8), (16, 8)]), data);
+
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert!(matches!(
+            vm.run(),
+            Err(RuntimeError::ArithmeticError { .. })
+        ));
+    }
+
+    #[test]
+    fn string_ops() {
+        use crate::inst::{ExitInst, Instruction, StrCatInst, StrEqInst, StrInst};
+
+        let chunk = Chunk::from(vec![
+            StrInst.opcode(),
+            0,
+            StrInst.opcode(),
+            2,
+            StrCatInst.opcode(),
+            StrInst.opcode(),
+            3,
+            StrEqInst.opcode(),
+            ExitInst.opcode(),
+        ]);
+        let pool = ConstantPool::new(HashMap::from([(0, 2), (2, 1), (3, 3)]), b"abcabc".to_vec());
+
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert_eq!(vm.run().unwrap(), 1);
+    }
+
+    #[test]
+    fn load_invalid_ref() {
+        use crate::inst::{ConstInst, Instruction, LoadInst};
+
+        let chunk = Chunk::from(vec![ConstInst.opcode(), 0, LoadInst.opcode(), 1]);
+        let pool = ConstantPool::new(HashMap::from([(0, 8)]), 100u64.to_be_bytes().to_vec());
+
+        let mut vm = VirtualMachine::new(chunk, pool);
+        assert!(matches!(
+            vm.run(),
+            Err(RuntimeError::InvalidRef { addr: 100 })
+        ));
+    }
+
     #[test]
     fn dyn_int_decode() {
         let dynint: &[u8] = &[0b1000_0001, 0b0000_1111];
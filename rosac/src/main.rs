@@ -1,33 +1,173 @@
-use std::{env, fs::read_to_string, path::PathBuf};
+use std::{env, fs, io, path::PathBuf, process::exit};
 
-use rosac_sema::SemanticAnalyzer;
+use rosa_comm::source_map::SourceMap;
+use rosa_errors::{codes::ErrCode, DiagCtxt};
+use rosac_sema::{
+    lint::{LintLevel, LintLevels},
+    module::ModuleGraph,
+};
 use termcolor::{ColorChoice, StandardStream};
 
-use rosa_errors::DiagCtxt;
-use rosac_lexer::{abs::BufferedLexer, Lexer};
+const USAGE: &str = "rosac [--search-path <dir>] [--error-format <human|json>] [--fix]
+             [--error-limit <count>] [-A|-W|-D <lint>]... [--dump-ast] <input file>
+       rosac --explain <error code>";
 
-use rosac_parser::Parser;
+/// How the diagnostics are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorFormat {
+    /// Rendered with the snippets of source code.
+    Human,
+    /// One JSON object per diagnostic and per line.
+    Json,
+}
+
+#[derive(Debug)]
+struct Options {
+    input: PathBuf,
+    search_path: PathBuf,
+    error_format: ErrorFormat,
+    /// Apply the machine-applicable suggestions to the source files.
+    fix: bool,
+    /// Maximum count of errors rendered.
+    error_limit: Option<usize>,
+    lint_levels: LintLevels,
+    /// Print the AST of the modules on the standard error.
+    dump_ast: bool,
+}
+
+fn parse_args() -> Options {
+    let mut input = None;
+    let mut search_path = None;
+    let mut error_format = ErrorFormat::Human;
+    let mut fix = false;
+    let mut error_limit = None;
+    let mut lint_levels = LintLevels::new();
+    let mut dump_ast = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--search-path" => match args.next() {
+                Some(dir) => search_path = Some(PathBuf::from(dir)),
+                None => usage(),
+            },
+            "--error-format" => match args.next().as_deref() {
+                Some("human") => error_format = ErrorFormat::Human,
+                Some("json") => error_format = ErrorFormat::Json,
+                _ => usage(),
+            },
+            "--fix" => fix = true,
+            "--error-limit" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(limit) => error_limit = Some(limit),
+                None => usage(),
+            },
+            "-A" | "-W" | "-D" => {
+                let level = match arg.as_str() {
+                    "-A" => LintLevel::Allow,
+                    "-W" => LintLevel::Warn,
+                    _ => LintLevel::Deny,
+                };
+                let Some(lint) = args.next() else { usage() };
+                if !lint_levels.set(&lint, level) {
+                    eprintln!("error: unknown lint `{lint}`");
+                    exit(2);
+                }
+            }
+            "--dump-ast" => dump_ast = true,
+            "--explain" => match args.next() {
+                Some(code) => explain(&code),
+                None => usage(),
+            },
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => usage(),
+        }
+    }
+
+    let Some(input) = input else { usage() };
+    // the modules are searched relative to the input file by default
+    let search_path =
+        search_path.unwrap_or_else(|| input.parent().map(|p| p.to_path_buf()).unwrap_or_default());
+
+    Options {
+        input,
+        search_path,
+        error_format,
+        fix,
+        error_limit,
+        lint_levels,
+        dump_ast,
+    }
+}
 
 fn main() {
-    println!("Hello, Rosa 🌹!\n");
+    let opts = parse_args();
+
+    // the standard output only contains the diagnostics in JSON.
+    if opts.error_format == ErrorFormat::Human {
+        println!("Hello, Rosa 🌹!\n");
+    }
 
-    let args: Vec<String> = env::args().collect();
-    assert_eq!(&args.len(), &2, "rosac <input file>");
-    let path = PathBuf::from(&args[1]);
-    let buf = read_to_string(&path).unwrap();
-    let mut s = StandardStream::stdout(ColorChoice::Auto);
+    let sm = SourceMap::new();
+    let mut dcx = DiagCtxt::new(&sm);
+    if let Some(limit) = opts.error_limit {
+        dcx.set_error_limit(limit);
+    }
 
-    let dcx = DiagCtxt::new(&buf, &path);
+    let graph = match ModuleGraph::load(&dcx, &opts.input, &opts.search_path) {
+        Ok(graph) => graph,
+        Err(err) => {
+            eprintln!("error: cannot read `{}`: {err}", opts.input.display());
+            exit(1);
+        }
+    };
 
-    let buf_lexer = BufferedLexer::new(Lexer::new(&path, &buf, &dcx));
+    graph.analyze(&dcx, &opts.lint_levels);
+    if opts.dump_ast {
+        for module in &graph.modules {
+            eprintln!("{}: {:#?}", module.name, module.ast);
+        }
+    }
 
-    let mut parser = Parser::new(buf_lexer);
+    match opts.error_format {
+        ErrorFormat::Human => {
+            let mut s = StandardStream::stdout(ColorChoice::Auto);
+            dcx.render_all(&mut s);
+        }
+        ErrorFormat::Json => dcx.emit_json(&mut io::stdout().lock()).unwrap(),
+    }
 
-    let mut ast = parser.begin_parsing();
+    if opts.fix {
+        for (file, src) in dcx.apply_suggestions() {
+            if let Err(err) = fs::write(&file.path, src) {
+                eprintln!("error: cannot write `{}`: {err}", file.path.display());
+                exit(1);
+            }
+            if opts.error_format == ErrorFormat::Human {
+                println!("fixed `{}`", file.path.display());
+            }
+        }
+    }
 
-    let mut seman = SemanticAnalyzer::new(&mut ast, &dcx);
-    dcx.emit_diags(seman.analyze());
-    dbg!(&ast);
+    if dcx.failed() {
+        exit(1);
+    }
+}
+
+/// Prints the explanation of the error code and exits.
+fn explain(code: &str) -> ! {
+    match ErrCode::parse(code).and_then(|code| code.explanation()) {
+        Some(text) => {
+            print!("{text}");
+            exit(0);
+        }
+        None => {
+            eprintln!("error: `{code}` is not a valid error code");
+            exit(1);
+        }
+    }
+}
 
-    dcx.render_all(&mut s);
+fn usage() -> ! {
+    eprintln!("usage: {USAGE}");
+    exit(2);
 }